@@ -13,7 +13,8 @@ pub enum StreamError {
     StreamNotFound = 2,
     /// Caller is not authorised to perform this action on the stream.
     Unauthorized = 3,
-    /// Operation requires an active stream, but the stream is inactive.
+    /// Legacy code from the former `is_active` flag. No longer returned;
+    /// superseded by the status-specific errors below.
     StreamInactive = 4,
     /// `initialize` has already been called; cannot re-initialize.
     AlreadyInitialized = 5,
@@ -25,4 +26,47 @@ pub enum StreamError {
     NotInitialized = 8,
     /// Duration supplied to `create_stream` is zero.
     InvalidDuration = 9,
+    /// Operation requires a cancelled (or, for `close_stream`, completed)
+    /// stream, but the stream is still live.
+    StreamActive = 10,
+    /// Stream schedule has already reached its end time.
+    StreamEnded = 11,
+    /// Operation is not allowed while the stream is paused.
+    StreamPaused = 12,
+    /// `resume_stream` called on a stream that is not paused.
+    StreamNotPaused = 13,
+    /// Cliff is before the stream start or after its end time.
+    InvalidCliff = 14,
+    /// Requested stream start time is in the past.
+    InvalidStartTime = 15,
+    /// Stream or subscription has been cancelled.
+    StreamCancelled = 16,
+    /// Stream has been fully withdrawn, or subscription has charged all its
+    /// periods.
+    StreamCompleted = 17,
+    /// Stream is still scheduled to start in the future.
+    StreamNotStarted = 18,
+    /// The stream's cancelability policy does not allow this caller to cancel
+    /// (or, for `NonCancelable` streams, the sender to pause).
+    NotCancelable = 19,
+    /// Stream still holds tokens owed to its recipient.
+    BalanceRemaining = 20,
+    /// No subscription exists for the supplied ID.
+    SubscriptionNotFound = 21,
+    /// The next subscription period is not due yet.
+    PaymentNotDue = 22,
+    /// `accept_admin` called with no admin proposal pending.
+    NoPendingAdmin = 23,
+    /// New deposits are blocked while the protocol is paused.
+    ProtocolPaused = 24,
+    /// `unpause_protocol` called while the protocol is not paused.
+    ProtocolNotPaused = 25,
+    /// The sender's allowance or balance cannot cover what an
+    /// allowance-funded stream owes its recipient.
+    FundingShortfall = 26,
+    /// Timestamp passed to `get_stream_balance` is in the past.
+    InvalidTimestamp = 27,
+    /// `ExtendDuration` top-up on a stream whose rate rounds down to 0, so
+    /// the extension would be unbounded.
+    ZeroRate = 28,
 }
@@ -1,4 +1,6 @@
-use soroban_sdk::{contracttype, Address};
+use soroban_sdk::{contracttype, Address, BytesN};
+
+use crate::types::Cancelability;
 
 /// Emitted when a new stream is created.
 ///
@@ -15,6 +17,11 @@ pub struct StreamCreatedEvent {
     /// Net deposited amount after protocol fee deduction.
     pub deposited_amount: i128,
     pub start_time: u64,
+    pub end_time: u64,
+    /// Equals `start_time` when the stream has no cliff.
+    pub cliff_time: u64,
+    /// Who may cancel the stream.
+    pub cancelability: Cancelability,
 }
 
 /// Emitted when a sender tops up an active stream.
@@ -29,6 +36,10 @@ pub struct StreamToppedUpEvent {
     pub amount: i128,
     /// Total deposited amount on the stream after this top-up.
     pub new_deposited_amount: i128,
+    /// Schedule end after this top-up.
+    pub end_time: u64,
+    /// Streaming rate after this top-up.
+    pub rate_per_second: i128,
 }
 
 /// Emitted when the recipient withdraws accrued tokens.
@@ -39,11 +50,13 @@ pub struct StreamToppedUpEvent {
 pub struct TokensWithdrawnEvent {
     pub stream_id: u64,
     pub recipient: Address,
+    /// Address that received the tokens (the recipient unless redirected).
+    pub to: Address,
     pub amount: i128,
     pub timestamp: u64,
 }
 
-/// Emitted when a sender cancels an active stream.
+/// Emitted when the sender or the recipient cancels a stream.
 ///
 /// Topic: `("stream_cancelled", stream_id)`
 #[contracttype]
@@ -52,12 +65,156 @@ pub struct StreamCancelledEvent {
     pub stream_id: u64,
     pub sender: Address,
     pub recipient: Address,
-    /// Total amount withdrawn by the recipient up to cancellation.
+    /// Party that cancelled: either `sender` or `recipient`.
+    pub cancelled_by: Address,
+    /// Total amount withdrawn by the recipient, including any accrual paid
+    /// out by the cancellation itself.
     pub amount_withdrawn: i128,
-    /// Unspent amount (deposited - withdrawn) returned to sender.
+    /// Accrued amount paid to the recipient by this cancellation; 0 when the
+    /// accrual was frozen for `claim_after_cancel` instead.
+    pub recipient_payout: i128,
+    /// Unaccrued amount returned to sender.
     pub refunded_amount: i128,
 }
 
+/// Emitted when the recipient claims the balance frozen at cancellation.
+///
+/// Topic: `("tokens_claimed", stream_id)`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TokensClaimedEvent {
+    pub stream_id: u64,
+    pub recipient: Address,
+    pub amount: i128,
+    pub timestamp: u64,
+}
+
+/// Emitted when the sender pauses accrual on a stream.
+///
+/// Topic: `("stream_paused", stream_id)`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StreamPausedEvent {
+    pub stream_id: u64,
+    pub sender: Address,
+    pub paused_at: u64,
+}
+
+/// Emitted when the sender resumes a paused stream.
+///
+/// Topic: `("stream_resumed", stream_id)`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StreamResumedEvent {
+    pub stream_id: u64,
+    pub sender: Address,
+    pub resumed_at: u64,
+    /// Schedule end after shifting by the paused duration.
+    pub end_time: u64,
+}
+
+/// Emitted when the recipient hands the stream over to a new recipient.
+///
+/// Topic: `("stream_transferred", stream_id)`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StreamTransferredEvent {
+    pub stream_id: u64,
+    /// Previous recipient.
+    pub from: Address,
+    /// New recipient, entitled to all accrual from now on.
+    pub to: Address,
+    /// Accrued amount paid out to `from` before the handover.
+    pub settled_amount: i128,
+}
+
+/// Emitted when the sender hands control of a stream to a new sender.
+///
+/// Topic: `("sender_transferred", stream_id)`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SenderTransferredEvent {
+    pub stream_id: u64,
+    /// Previous sender.
+    pub from: Address,
+    /// New sender, entitled to top up, cancel and receive refunds.
+    pub to: Address,
+}
+
+/// Emitted when a settled stream is removed from storage by `close_stream`.
+///
+/// Topic: `("stream_closed", stream_id)`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StreamClosedEvent {
+    pub stream_id: u64,
+    pub sender: Address,
+    pub recipient: Address,
+    pub closed_by: Address,
+    /// Total amount the recipient received over the stream's lifetime.
+    pub total_withdrawn: i128,
+}
+
+/// Emitted when a subscription is created.
+///
+/// Topic: `("subscription_created", subscription_id)`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SubscriptionCreatedEvent {
+    pub subscription_id: u64,
+    pub subscriber: Address,
+    pub merchant: Address,
+    pub token_address: Address,
+    pub amount_per_period: i128,
+    pub period: u64,
+    pub max_periods: u32,
+    pub start_time: u64,
+}
+
+/// Emitted each time a subscription period is charged.
+///
+/// Topic: `("subscription_charged", subscription_id)`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SubscriptionChargedEvent {
+    pub subscription_id: u64,
+    pub subscriber: Address,
+    pub merchant: Address,
+    pub amount: i128,
+    /// Number of periods charged so far, including this one.
+    pub periods_charged: u32,
+    pub timestamp: u64,
+}
+
+/// Emitted when a subscription is cancelled.
+///
+/// Topic: `("subscription_cancelled", subscription_id)`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SubscriptionCancelledEvent {
+    pub subscription_id: u64,
+    pub subscriber: Address,
+    pub merchant: Address,
+    pub cancelled_by: Address,
+    pub periods_charged: u32,
+}
+
+/// Emitted when the sender of an allowance-funded stream cannot cover a
+/// withdrawal. The stream stops accruing and becomes `Depleted`.
+///
+/// Topic: `("funding_shortfall", stream_id)`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FundingShortfallEvent {
+    pub stream_id: u64,
+    pub sender: Address,
+    /// Amount the withdrawal asked for.
+    pub requested: i128,
+    /// Amount the sender's allowance and balance could cover (and was paid).
+    pub available: i128,
+    pub timestamp: u64,
+}
+
 /// Emitted when a protocol fee is collected during create or top-up.
 ///
 /// Topic: `("fee_collected", stream_id)`
@@ -69,3 +226,72 @@ pub struct FeeCollectedEvent {
     pub fee_amount: i128,
     pub token: Address,
 }
+
+/// Emitted when the admin proposes a successor.
+///
+/// Topic: `("admin_proposed",)`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AdminProposedEvent {
+    pub admin: Address,
+    pub pending_admin: Address,
+}
+
+/// Emitted when a proposed admin accepts and takes over.
+///
+/// Topic: `("admin_transferred",)`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AdminTransferredEvent {
+    pub previous_admin: Address,
+    pub new_admin: Address,
+}
+
+/// Emitted when the admin renounces admin rights permanently.
+///
+/// Topic: `("admin_renounced",)`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AdminRenouncedEvent {
+    pub admin: Address,
+}
+
+/// Emitted when the admin pauses new deposits protocol-wide.
+///
+/// Topic: `("protocol_paused",)`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProtocolPausedEvent {
+    pub admin: Address,
+    pub timestamp: u64,
+}
+
+/// Emitted when the admin lifts a protocol-wide pause.
+///
+/// Topic: `("protocol_unpaused",)`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProtocolUnpausedEvent {
+    pub admin: Address,
+    pub timestamp: u64,
+}
+
+/// Emitted when the admin replaces the contract code.
+///
+/// Topic: `("contract_upgraded",)`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ContractUpgradedEvent {
+    pub admin: Address,
+    pub new_wasm_hash: BytesN<32>,
+}
+
+/// Emitted when `migrate` moves instance storage to a newer schema.
+///
+/// Topic: `("schema_migrated",)`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SchemaMigratedEvent {
+    pub from_version: u32,
+    pub to_version: u32,
+}
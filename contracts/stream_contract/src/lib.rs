@@ -1,4 +1,7 @@
 #![no_std]
+// Stream-creation entrypoints take the full schedule as arguments, and the
+// client code generated by `#[contractimpl]` mirrors those signatures.
+#![allow(clippy::too_many_arguments)]
 
 mod errors;
 mod events;
@@ -8,22 +11,35 @@ mod types;
 #[cfg(test)]
 mod test;
 
-use soroban_sdk::{contract, contractimpl, token, Address, Env, Symbol};
+use soroban_sdk::{contract, contractimpl, token, Address, BytesN, Env, Map, Symbol, Vec};
 
 use errors::StreamError;
 use events::{
-    FeeCollectedEvent, StreamCancelledEvent, StreamCreatedEvent, StreamToppedUpEvent,
-    TokensWithdrawnEvent,
+    AdminProposedEvent, AdminRenouncedEvent, AdminTransferredEvent, ContractUpgradedEvent,
+    FeeCollectedEvent, FundingShortfallEvent, ProtocolPausedEvent, ProtocolUnpausedEvent,
+    SchemaMigratedEvent, SenderTransferredEvent, StreamCancelledEvent, StreamClosedEvent,
+    StreamCreatedEvent, StreamPausedEvent, StreamResumedEvent, StreamToppedUpEvent,
+    StreamTransferredEvent, SubscriptionCancelledEvent, SubscriptionChargedEvent,
+    SubscriptionCreatedEvent, TokensClaimedEvent, TokensWithdrawnEvent,
 };
 use storage::{
-    config_exists, load_config, load_stream, next_stream_id, save_config, save_stream,
-    try_load_config, try_load_stream,
+    add_to_index, clear_pending_admin, config_exists, extend_stream_ttl, load_config, load_index,
+    load_pending_admin, load_schema_version, load_stream, load_subscription, migrate_instance,
+    next_stream_id, next_subscription_id, remove_from_index, remove_stream, save_config,
+    save_pending_admin, save_schema_version, save_stream, save_subscription, try_load_config,
+    try_load_stream, try_load_subscription, StreamIndex, SCHEMA_VERSION,
+};
+use types::{
+    Cancelability, FundingMode, ProtocolConfig, Stream, StreamBalance, StreamParams, StreamStatus,
+    Subscription, SubscriptionStatus, TopUpMode, WithdrawalResult,
 };
-use types::{ProtocolConfig, Stream};
 
 /// Maximum allowed protocol fee: 1 000 bps = 10%.
 const MAX_FEE_RATE_BPS: u32 = 1_000;
 
+/// Maximum number of stream IDs returned by one page of an address index query.
+const MAX_PAGE_SIZE: u32 = 100;
+
 #[contract]
 pub struct StreamContract;
 
@@ -54,11 +70,13 @@ impl StreamContract {
         save_config(
             &env,
             &ProtocolConfig {
-                admin,
+                admin: Some(admin),
                 treasury,
                 fee_rate_bps,
+                paused: false,
             },
         );
+        save_schema_version(&env, SCHEMA_VERSION);
         Ok(())
     }
 
@@ -74,12 +92,7 @@ impl StreamContract {
         treasury: Address,
         fee_rate_bps: u32,
     ) -> Result<(), StreamError> {
-        admin.require_auth();
-
-        let config = load_config(&env)?;
-        if config.admin != admin {
-            return Err(StreamError::NotAdmin);
-        }
+        let config = Self::require_admin(&env, &admin)?;
         if fee_rate_bps > MAX_FEE_RATE_BPS {
             return Err(StreamError::InvalidFeeRate);
         }
@@ -90,6 +103,7 @@ impl StreamContract {
                 admin: config.admin,
                 treasury,
                 fee_rate_bps,
+                paused: config.paused,
             },
         );
         Ok(())
@@ -100,13 +114,223 @@ impl StreamContract {
         try_load_config(&env)
     }
 
+    /// Emergency stop: block new deposits protocol-wide. Admin-only.
+    ///
+    /// While paused, stream creation and `top_up_stream` fail with
+    /// `ProtocolPaused`. Withdrawals, claims and cancellations keep working
+    /// so users can always exit.
+    ///
+    /// # Errors
+    /// - `NotInitialized` — `initialize` has not been called.
+    /// - `NotAdmin`       — caller is not the current admin.
+    /// - `ProtocolPaused` — the protocol is already paused.
+    pub fn pause_protocol(env: Env, admin: Address) -> Result<(), StreamError> {
+        let mut config = Self::require_admin(&env, &admin)?;
+        if config.paused {
+            return Err(StreamError::ProtocolPaused);
+        }
+
+        config.paused = true;
+        save_config(&env, &config);
+
+        env.events().publish(
+            (Symbol::new(&env, "protocol_paused"),),
+            ProtocolPausedEvent {
+                admin,
+                timestamp: env.ledger().timestamp(),
+            },
+        );
+        Ok(())
+    }
+
+    /// Lift an emergency stop set by `pause_protocol`. Admin-only.
+    ///
+    /// # Errors
+    /// - `NotInitialized`    — `initialize` has not been called.
+    /// - `NotAdmin`          — caller is not the current admin.
+    /// - `ProtocolNotPaused` — the protocol is not paused.
+    pub fn unpause_protocol(env: Env, admin: Address) -> Result<(), StreamError> {
+        let mut config = Self::require_admin(&env, &admin)?;
+        if !config.paused {
+            return Err(StreamError::ProtocolNotPaused);
+        }
+
+        config.paused = false;
+        save_config(&env, &config);
+
+        env.events().publish(
+            (Symbol::new(&env, "protocol_unpaused"),),
+            ProtocolUnpausedEvent {
+                admin,
+                timestamp: env.ledger().timestamp(),
+            },
+        );
+        Ok(())
+    }
+
+    /// Propose `new_admin` as the next protocol admin. Admin-only.
+    ///
+    /// Takes effect only once `new_admin` calls `accept_admin`, so a typo
+    /// cannot lock the protocol. A later proposal replaces an earlier one.
+    ///
+    /// # Errors
+    /// - `NotInitialized` — `initialize` has not been called.
+    /// - `NotAdmin`       — caller is not the current admin.
+    pub fn propose_admin(env: Env, admin: Address, new_admin: Address) -> Result<(), StreamError> {
+        Self::require_admin(&env, &admin)?;
+
+        save_pending_admin(&env, &new_admin);
+
+        env.events().publish(
+            (Symbol::new(&env, "admin_proposed"),),
+            AdminProposedEvent {
+                admin,
+                pending_admin: new_admin,
+            },
+        );
+        Ok(())
+    }
+
+    /// Accept a pending admin proposal, becoming the protocol admin.
+    ///
+    /// # Errors
+    /// - `NotInitialized` — `initialize` has not been called.
+    /// - `NoPendingAdmin` — no admin proposal is pending.
+    /// - `NotAdmin`       — caller is not the proposed admin.
+    pub fn accept_admin(env: Env, new_admin: Address) -> Result<(), StreamError> {
+        new_admin.require_auth();
+
+        let mut config = load_config(&env)?;
+        let pending = load_pending_admin(&env).ok_or(StreamError::NoPendingAdmin)?;
+        if pending != new_admin {
+            return Err(StreamError::NotAdmin);
+        }
+
+        // A pending proposal implies an admin: renouncing clears it.
+        let previous_admin = config.admin.clone().ok_or(StreamError::NotAdmin)?;
+        config.admin = Some(new_admin.clone());
+        save_config(&env, &config);
+        clear_pending_admin(&env);
+
+        env.events().publish(
+            (Symbol::new(&env, "admin_transferred"),),
+            AdminTransferredEvent {
+                previous_admin,
+                new_admin,
+            },
+        );
+        Ok(())
+    }
+
+    /// Give up admin rights permanently. Admin-only.
+    ///
+    /// Afterwards the fee configuration is frozen and no admin-only
+    /// entrypoint can be called again. Any pending proposal is discarded.
+    /// Not allowed while the protocol is paused, since nobody could unpause
+    /// it afterwards.
+    ///
+    /// # Errors
+    /// - `NotInitialized` — `initialize` has not been called.
+    /// - `NotAdmin`       — caller is not the current admin.
+    /// - `ProtocolPaused` — the protocol is paused; unpause it first.
+    pub fn renounce_admin(env: Env, admin: Address) -> Result<(), StreamError> {
+        let mut config = Self::require_admin(&env, &admin)?;
+        if config.paused {
+            return Err(StreamError::ProtocolPaused);
+        }
+
+        config.admin = None;
+        save_config(&env, &config);
+        clear_pending_admin(&env);
+
+        env.events().publish(
+            (Symbol::new(&env, "admin_renounced"),),
+            AdminRenouncedEvent { admin },
+        );
+        Ok(())
+    }
+
+    /// Returns the admin proposed by `propose_admin` and not yet accepted, if any.
+    pub fn get_pending_admin(env: Env) -> Option<Address> {
+        load_pending_admin(&env)
+    }
+
+    /// Replace the contract code with the already-uploaded WASM identified by
+    /// `new_wasm_hash`. Admin-only.
+    ///
+    /// Storage is kept as is. If the new code bumps the storage schema, call
+    /// `migrate` right after (ideally in the same transaction): entrypoints
+    /// that read the protocol config fail until it runs. Stream records
+    /// migrate lazily as they are loaded.
+    ///
+    /// # Errors
+    /// - `NotInitialized` — `initialize` has not been called.
+    /// - `NotAdmin`       — caller is not the current admin.
+    pub fn upgrade(env: Env, admin: Address, new_wasm_hash: BytesN<32>) -> Result<(), StreamError> {
+        Self::require_admin(&env, &admin)?;
+
+        env.deployer()
+            .update_current_contract_wasm(new_wasm_hash.clone());
+
+        env.events().publish(
+            (Symbol::new(&env, "contract_upgraded"),),
+            ContractUpgradedEvent {
+                admin,
+                new_wasm_hash,
+            },
+        );
+        Ok(())
+    }
+
+    /// Bring instance storage up to this build's schema version after an
+    /// `upgrade`. Admin-only; a no-op when already current.
+    ///
+    /// Returns the schema version now in effect.
+    ///
+    /// # Errors
+    /// - `NotInitialized` — `initialize` has not been called.
+    /// - `NotAdmin`       — caller is not the current admin.
+    pub fn migrate(env: Env, admin: Address) -> Result<u32, StreamError> {
+        // The config may still be in an old layout, so migrate first and
+        // check the admin against the result; a failed check reverts it all.
+        let from_version = load_schema_version(&env);
+        if from_version < SCHEMA_VERSION {
+            migrate_instance(&env, from_version);
+            save_schema_version(&env, SCHEMA_VERSION);
+        }
+
+        Self::require_admin(&env, &admin)?;
+        if from_version >= SCHEMA_VERSION {
+            return Ok(from_version);
+        }
+
+        env.events().publish(
+            (Symbol::new(&env, "schema_migrated"),),
+            SchemaMigratedEvent {
+                from_version,
+                to_version: SCHEMA_VERSION,
+            },
+        );
+        Ok(SCHEMA_VERSION)
+    }
+
+    /// Returns the storage schema version instance storage is at; 0 if it
+    /// predates schema versioning.
+    pub fn get_schema_version(env: Env) -> u32 {
+        load_schema_version(&env)
+    }
+
     // ─── Stream Operations ────────────────────────────────────────────────────
 
     /// Create a new payment stream.
     ///
     /// Transfers `amount` tokens from `sender` to the contract, deducts the
     /// protocol fee (if configured), and records the stream with a calculated
-    /// `rate_per_second = net_amount / duration`.
+    /// `rate_per_second = net_amount / duration` and
+    /// `end_time = start_time + duration`.
+    ///
+    /// `cancelability` fixes who may cancel the stream for its whole life;
+    /// `NonCancelable` guarantees the recipient the full deposit.
     ///
     /// Returns the new stream ID (starts at 1, increments monotonically).
     ///
@@ -120,98 +344,320 @@ impl StreamContract {
         token_address: Address,
         amount: i128,
         duration: u64,
+        cancelability: Cancelability,
     ) -> Result<u64, StreamError> {
         sender.require_auth();
 
-        if amount <= 0 {
-            return Err(StreamError::InvalidAmount);
-        }
-        if duration == 0 {
-            return Err(StreamError::InvalidDuration);
-        }
+        Self::open_stream(
+            &env,
+            sender,
+            token_address,
+            StreamParams {
+                recipient,
+                amount,
+                start_time: None,
+                duration,
+                cliff_time: None,
+                cancelability,
+            },
+            FundingMode::Escrowed,
+        )
+    }
 
-        let stream_id = next_stream_id(&env);
-        let start_time = env.ledger().timestamp();
+    /// Create a vesting-style stream with a cliff.
+    ///
+    /// Accrual follows the same schedule as `create_stream`, but nothing is
+    /// claimable before `cliff_time`. At the cliff, everything accrued since
+    /// `start_time` unlocks at once. Cancelling before the cliff refunds the
+    /// full deposit to the sender.
+    ///
+    /// # Errors
+    /// - `InvalidAmount`   — `amount` ≤ 0.
+    /// - `InvalidDuration` — `duration` is 0.
+    /// - `InvalidCliff`    — `cliff_time` is before the start or after the end.
+    pub fn create_stream_with_cliff(
+        env: Env,
+        sender: Address,
+        recipient: Address,
+        token_address: Address,
+        amount: i128,
+        duration: u64,
+        cliff_time: u64,
+        cancelability: Cancelability,
+    ) -> Result<u64, StreamError> {
+        sender.require_auth();
 
-        // Transfer gross amount from sender to this contract.
-        let token_client = token::Client::new(&env, &token_address);
-        let contract_address = env.current_contract_address();
-        token_client.transfer(&sender, &contract_address, &amount);
+        Self::open_stream(
+            &env,
+            sender,
+            token_address,
+            StreamParams {
+                recipient,
+                amount,
+                start_time: None,
+                duration,
+                cliff_time: Some(cliff_time),
+                cancelability,
+            },
+            FundingMode::Escrowed,
+        )
+    }
 
-        // Deduct protocol fee; returns net amount (== amount when no fee config).
-        let net_amount = Self::collect_fee(&env, &token_address, amount, stream_id);
-        let rate_per_second = net_amount / (duration as i128);
+    /// Create a stream that starts accruing at a future `start_time`.
+    ///
+    /// Funds are escrowed immediately, but nothing accrues until
+    /// `start_time`. Cancelling before the start refunds the full deposit to
+    /// the sender.
+    ///
+    /// # Errors
+    /// - `InvalidAmount`    — `amount` ≤ 0.
+    /// - `InvalidDuration`  — `duration` is 0.
+    /// - `InvalidStartTime` — `start_time` is in the past.
+    pub fn create_delayed_stream(
+        env: Env,
+        sender: Address,
+        recipient: Address,
+        token_address: Address,
+        amount: i128,
+        start_time: u64,
+        duration: u64,
+        cancelability: Cancelability,
+    ) -> Result<u64, StreamError> {
+        sender.require_auth();
 
-        save_stream(
+        Self::open_stream(
             &env,
-            stream_id,
-            &Stream {
-                sender: sender.clone(),
-                recipient: recipient.clone(),
-                token_address: token_address.clone(),
-                rate_per_second,
-                deposited_amount: net_amount,
-                withdrawn_amount: 0,
-                start_time,
-                last_update_time: start_time,
-                is_active: true,
+            sender,
+            token_address,
+            StreamParams {
+                recipient,
+                amount,
+                start_time: Some(start_time),
+                duration,
+                cliff_time: None,
+                cancelability,
             },
-        );
+            FundingMode::Escrowed,
+        )
+    }
 
-        env.events().publish(
-            (Symbol::new(&env, "stream_created"), stream_id),
-            StreamCreatedEvent {
-                stream_id,
-                sender,
+    /// Create a stream that is funded from the sender's account as it is
+    /// withdrawn, instead of being escrowed up front.
+    ///
+    /// `amount` caps what the stream can pay out, and the schedule is the same
+    /// as for `create_stream`. Nothing is escrowed; the sender must approve
+    /// this contract to spend at least `amount` (e.g. via the token's
+    /// `approve`), and each withdrawal pulls the accrued tokens straight
+    /// from the sender with `transfer_from`. The protocol fee is still paid
+    /// up front, directly from the sender.
+    ///
+    /// If a withdrawal finds the sender's allowance or balance short, it pays
+    /// what is available, stops accrual at that point, marks the stream
+    /// `Depleted`, and emits `funding_shortfall`. The recipient remains owed
+    /// the rest, payable once the sender restores funds. Cancellation and
+    /// `claim_after_cancel` behave the same way: whatever cannot be covered
+    /// stays claimable via `claim_after_cancel`.
+    ///
+    /// `cancelability` works as for `create_stream`, but without escrow even
+    /// `NonCancelable` cannot guarantee the recipient the full `amount`: the
+    /// sender can revoke the allowance or move the funds at any time.
+    ///
+    /// # Errors
+    /// - `InvalidAmount`   — `amount` ≤ 0.
+    /// - `InvalidDuration` — `duration` is 0.
+    pub fn create_allowance_stream(
+        env: Env,
+        sender: Address,
+        recipient: Address,
+        token_address: Address,
+        amount: i128,
+        duration: u64,
+        cancelability: Cancelability,
+    ) -> Result<u64, StreamError> {
+        sender.require_auth();
+
+        Self::open_stream(
+            &env,
+            sender,
+            token_address,
+            StreamParams {
                 recipient,
-                rate_per_second,
-                token_address,
-                deposited_amount: net_amount,
-                start_time,
+                amount,
+                start_time: None,
+                duration,
+                cliff_time: None,
+                cancelability,
             },
-        );
+            FundingMode::Allowance,
+        )
+    }
 
-        Ok(stream_id)
+    /// Create many streams of the same token in one call, e.g. for payroll.
+    ///
+    /// The combined gross amount is pulled from `sender` in a single
+    /// transfer, and the protocol fees of all streams are sent to the
+    /// treasury in a single transfer. Each stream gets its own consecutive ID
+    /// and `stream_created` event, and is otherwise identical to one created
+    /// individually with the same parameters.
+    ///
+    /// Every entry is validated before any funds move; one invalid entry
+    /// fails the whole batch.
+    ///
+    /// Returns the new stream IDs in the order of `streams`.
+    ///
+    /// # Errors
+    /// - `InvalidAmount`    — `streams` is empty, an `amount` ≤ 0, or the
+    ///   total overflows.
+    /// - `InvalidDuration`  — a `duration` is 0.
+    /// - `InvalidStartTime` — a `start_time` is in the past.
+    /// - `InvalidCliff`     — a `cliff_time` is before its start or after its end.
+    pub fn create_streams_batch(
+        env: Env,
+        sender: Address,
+        token_address: Address,
+        streams: Vec<StreamParams>,
+    ) -> Result<Vec<u64>, StreamError> {
+        sender.require_auth();
+
+        Self::ensure_protocol_active(&env)?;
+        if streams.is_empty() {
+            return Err(StreamError::InvalidAmount);
+        }
+
+        let mut total: i128 = 0;
+        for params in streams.iter() {
+            Self::resolve_schedule(&env, &params)?;
+            total = total
+                .checked_add(params.amount)
+                .ok_or(StreamError::InvalidAmount)?;
+        }
+
+        let token_client = token::Client::new(&env, &token_address);
+        let contract_address = env.current_contract_address();
+        token_client.transfer(&sender, &contract_address, &total);
+
+        let config = try_load_config(&env);
+        let mut total_fee: i128 = 0;
+        let mut stream_ids = Vec::new(&env);
+
+        for params in streams.iter() {
+            let schedule = Self::resolve_schedule(&env, &params)?;
+            let stream_id = next_stream_id(&env);
+
+            let fee = match &config {
+                Some(cfg) => {
+                    let fee = Self::fee_for(cfg, params.amount);
+                    if fee > 0 {
+                        Self::emit_fee_collected(&env, cfg, &token_address, fee, stream_id);
+                    }
+                    fee
+                }
+                None => 0,
+            };
+            total_fee += fee;
+
+            let net_amount = params.amount - fee;
+            Self::record_stream(
+                &env,
+                stream_id,
+                sender.clone(),
+                token_address.clone(),
+                params,
+                schedule,
+                net_amount,
+                FundingMode::Escrowed,
+            );
+            stream_ids.push_back(stream_id);
+        }
+
+        if let Some(cfg) = config {
+            if total_fee > 0 {
+                token_client.transfer(&contract_address, &cfg.treasury, &total_fee);
+            }
+        }
+
+        Ok(stream_ids)
     }
 
     /// Top up an active stream with additional tokens.
     ///
-    /// Only the original sender may top up their own stream.
+    /// Only the original sender may top up their own stream. `mode` selects
+    /// how the extra funds are scheduled:
+    /// - `ExtendDuration` keeps the rate and pushes `end_time` out. If the
+    ///   schedule has already ended, the top-up streams from now. Not
+    ///   available when the rate rounds down to 0 (deposit smaller than the
+    ///   duration), since the extension would be unbounded.
+    /// - `RaiseRate` keeps `end_time` and raises the rate so the remaining
+    ///   balance is streamed by then.
+    ///
+    /// Accrual already earned by the recipient is unaffected in both modes.
     ///
     /// # Errors
     /// - `InvalidAmount`   — `amount` ≤ 0.
     /// - `StreamNotFound`  — no stream exists with `stream_id`.
     /// - `Unauthorized`    — caller is not the stream's sender.
-    /// - `StreamInactive`  — stream has been cancelled or fully withdrawn.
+    /// - `StreamCancelled` — stream has been cancelled.
+    /// - `StreamCompleted` — stream has been fully withdrawn.
+    /// - `StreamPaused`    — stream is paused; resume it first.
+    /// - `StreamEnded`     — `RaiseRate` requested on a `Depleted` stream.
+    /// - `ZeroRate`        — `ExtendDuration` requested on a stream whose
+    ///   rate is 0.
     pub fn top_up_stream(
         env: Env,
         sender: Address,
         stream_id: u64,
         amount: i128,
+        mode: TopUpMode,
     ) -> Result<(), StreamError> {
         sender.require_auth();
 
+        Self::ensure_protocol_active(&env)?;
         if amount <= 0 {
             return Err(StreamError::InvalidAmount);
         }
 
-        let mut stream = load_stream(&env, stream_id)?;
+        let mut stream = Self::load_current(&env, stream_id)?;
 
         if stream.sender != sender {
             return Err(StreamError::Unauthorized);
         }
-        if !stream.is_active {
-            return Err(StreamError::StreamInactive);
+        Self::ensure_not_terminal(&stream)?;
+        if stream.status == StreamStatus::Paused {
+            return Err(StreamError::StreamPaused);
+        }
+        if mode == TopUpMode::RaiseRate && stream.status == StreamStatus::Depleted {
+            return Err(StreamError::StreamEnded);
+        }
+        if mode == TopUpMode::ExtendDuration && stream.rate_per_second == 0 {
+            return Err(StreamError::ZeroRate);
         }
 
-        let token_client = token::Client::new(&env, &stream.token_address);
-        let contract_address = env.current_contract_address();
-        token_client.transfer(&sender, &contract_address, &amount);
+        let now = env.ledger().timestamp();
 
-        let net_amount = Self::collect_fee(&env, &stream.token_address, amount, stream_id);
+        let net_amount = Self::fund(
+            &env,
+            &sender,
+            &stream.token_address,
+            amount,
+            stream.funding,
+            stream_id,
+        );
 
+        Self::pin_checkpoint(&mut stream, now);
         stream.deposited_amount += net_amount;
-        stream.last_update_time = env.ledger().timestamp();
+
+        match mode {
+            TopUpMode::ExtendDuration => {
+                stream.end_time = Self::schedule_end(&stream);
+            }
+            TopUpMode::RaiseRate => {
+                let remaining = stream.deposited_amount - stream.checkpoint_streamed;
+                let remaining_time = stream.end_time - stream.checkpoint_time;
+                stream.rate_per_second = remaining / (remaining_time as i128);
+            }
+        }
+        Self::sync_status(&mut stream, now);
+        stream.last_update_time = now;
 
         save_stream(&env, stream_id, &stream);
 
@@ -222,6 +668,8 @@ impl StreamContract {
                 sender,
                 amount: net_amount,
                 new_deposited_amount: stream.deposited_amount,
+                end_time: stream.end_time,
+                rate_per_second: stream.rate_per_second,
             },
         );
 
@@ -230,45 +678,133 @@ impl StreamContract {
 
     // ─── Internal Helpers ─────────────────────────────────────────────────────
 
-    fn calculate_claimable(stream: &Stream, now: u64) -> i128 {
-        let elapsed = now.saturating_sub(stream.last_update_time);
+    /// Cumulative amount streamed to the recipient by timestamp `t`.
+    ///
+    /// Follows `scheduled_at`, except that nothing is released before
+    /// `cliff_time`.
+    fn streamed_at(stream: &Stream, t: u64) -> i128 {
+        if Self::accrual_time(stream, t) < stream.cliff_time {
+            return 0;
+        }
+        Self::scheduled_at(stream, t)
+    }
+
+    /// Cumulative amount the schedule has accrued by timestamp `t`, ignoring
+    /// the cliff.
+    ///
+    /// Derived purely from the schedule (`checkpoint_time`,
+    /// `checkpoint_streamed`, `end_time`, `rate_per_second`) and capped at
+    /// `deposited_amount`, so the result is independent of when withdrawals
+    /// or top-ups happened.
+    ///
+    /// `rate_per_second` is rounded down, so at `end_time` the full
+    /// `deposited_amount` is released, including any truncation remainder.
+    fn scheduled_at(stream: &Stream, t: u64) -> i128 {
+        let t = Self::accrual_time(stream, t);
+        if t <= stream.start_time {
+            return 0;
+        }
+        if t >= stream.end_time {
+            return stream.deposited_amount;
+        }
+        let elapsed = t
+            .min(stream.end_time)
+            .saturating_sub(stream.checkpoint_time);
 
         let streamed = (elapsed as i128)
             .checked_mul(stream.rate_per_second)
+            .and_then(|s| s.checked_add(stream.checkpoint_streamed))
             .unwrap_or(i128::MAX);
 
-        let remaining = stream
-            .deposited_amount
-            .saturating_sub(stream.withdrawn_amount);
+        streamed.min(stream.deposited_amount)
+    }
 
-        if streamed > remaining {
-            remaining
-        } else {
-            streamed
+    /// Clamps `t` to `paused_at` while the stream is paused, freezing accrual.
+    fn accrual_time(stream: &Stream, t: u64) -> u64 {
+        match stream.status {
+            StreamStatus::Paused => t.min(stream.paused_at),
+            _ => t,
+        }
+    }
+
+    /// Pins the amount accrued by `now` as the new checkpoint so a subsequent
+    /// schedule change only affects the future. No-op before `start_time`,
+    /// where the original checkpoint still holds.
+    fn pin_checkpoint(stream: &mut Stream, now: u64) {
+        if now > stream.checkpoint_time {
+            stream.checkpoint_streamed = Self::scheduled_at(stream, now);
+            stream.checkpoint_time = now;
+        }
+    }
+
+    /// Amount the recipient can withdraw at `now`: streamed minus withdrawn.
+    fn calculate_claimable(stream: &Stream, now: u64) -> i128 {
+        Self::streamed_at(stream, now)
+            .saturating_sub(stream.withdrawn_amount)
+            .max(0)
+    }
+
+    /// Evaluates the stream's schedule at `at`.
+    ///
+    /// `refundable` mirrors what `settle_cancellation` would return to the
+    /// sender at `at`.
+    fn balance_at(stream: &Stream, at: u64) -> StreamBalance {
+        let streamed = Self::streamed_at(stream, at);
+        let claimable = Self::calculate_claimable(stream, at);
+        // Allowance streams hold no escrow, so there is nothing to refund.
+        let refundable = match (stream.funding, stream.status) {
+            (FundingMode::Allowance, _)
+            | (_, StreamStatus::Cancelled | StreamStatus::Completed) => 0,
+            _ => stream
+                .deposited_amount
+                .saturating_sub(stream.withdrawn_amount)
+                .saturating_sub(claimable),
+        };
+        StreamBalance {
+            timestamp: at,
+            deposited: stream.deposited_amount,
+            streamed,
+            withdrawn: stream.withdrawn_amount,
+            claimable,
+            refundable,
+        }
+    }
+
+    /// Earliest timestamp at which `deposited_amount` is fully streamed at the
+    /// current rate, counting from the checkpoint. Returns the existing
+    /// `end_time` when the rate is zero.
+    fn schedule_end(stream: &Stream) -> u64 {
+        if stream.rate_per_second <= 0 {
+            return stream.end_time;
         }
+        let rate = stream.rate_per_second;
+        let unstreamed = stream.deposited_amount - stream.checkpoint_streamed;
+        let duration = (unstreamed + rate - 1) / rate;
+        stream
+            .checkpoint_time
+            .saturating_add(u64::try_from(duration).unwrap_or(u64::MAX))
     }
 
     /// Withdraw all currently claimable tokens from a stream.
     ///
-    /// Only the stream's recipient may call this. The stream is marked
-    /// inactive once fully drained.
+    /// Only the stream's recipient may call this. The stream becomes
+    /// `Completed` once fully drained.
+    ///
+    /// Returns the amount paid. For an allowance-funded stream this is less
+    /// than the claimable balance when the sender is short of funds; see
+    /// `create_allowance_stream`.
     ///
     /// # Errors
-    /// - `StreamNotFound`  — no stream exists with `stream_id`.
-    /// - `Unauthorized`    — caller is not the stream's recipient.
-    /// - `StreamInactive`  — stream is already inactive.
-    /// - `InvalidAmount`   — no claimable balance (fully withdrawn already).
+    /// - `StreamNotFound`   — no stream exists with `stream_id`.
+    /// - `Unauthorized`     — caller is not the stream's recipient.
+    /// - `StreamNotStarted` — stream is still `Scheduled`.
+    /// - `StreamCancelled`  — stream has been cancelled.
+    /// - `StreamCompleted`  — stream has been fully withdrawn.
+    /// - `InvalidAmount`    — no claimable balance.
     pub fn withdraw(env: Env, recipient: Address, stream_id: u64) -> Result<i128, StreamError> {
         recipient.require_auth();
 
-        let mut stream = load_stream(&env, stream_id)?;
-
-        if stream.recipient != recipient {
-            return Err(StreamError::Unauthorized);
-        }
-        if !stream.is_active {
-            return Err(StreamError::StreamInactive);
-        }
+        let mut stream = Self::load_withdrawable(&env, &recipient, stream_id)?;
 
         let now = env.ledger().timestamp();
         let claimable = Self::calculate_claimable(&stream, now);
@@ -277,86 +813,746 @@ impl StreamContract {
             return Err(StreamError::InvalidAmount);
         }
 
-        let token_client = token::Client::new(&env, &stream.token_address);
+        Ok(Self::pay_out(
+            &env,
+            stream_id,
+            &mut stream,
+            claimable,
+            recipient,
+            now,
+        ))
+    }
+
+    /// Withdraw part of the claimable balance, optionally to another address.
+    ///
+    /// Only the stream's recipient may call this. Tokens are sent to `to`, or
+    /// to the recipient when `to` is `None`. Any remainder stays claimable.
+    ///
+    /// Returns the amount paid, which may be less than `amount` for an
+    /// allowance-funded stream whose sender is short of funds.
+    ///
+    /// # Errors
+    /// - `StreamNotFound`   — no stream exists with `stream_id`.
+    /// - `Unauthorized`     — caller is not the stream's recipient.
+    /// - `StreamNotStarted` — stream is still `Scheduled`.
+    /// - `StreamCancelled`  — stream has been cancelled.
+    /// - `StreamCompleted`  — stream has been fully withdrawn.
+    /// - `InvalidAmount`    — `amount` ≤ 0 or exceeds the claimable balance.
+    pub fn withdraw_amount(
+        env: Env,
+        recipient: Address,
+        stream_id: u64,
+        amount: i128,
+        to: Option<Address>,
+    ) -> Result<i128, StreamError> {
+        recipient.require_auth();
+
+        if amount <= 0 {
+            return Err(StreamError::InvalidAmount);
+        }
+
+        let mut stream = Self::load_withdrawable(&env, &recipient, stream_id)?;
+
+        let now = env.ledger().timestamp();
+        if amount > Self::calculate_claimable(&stream, now) {
+            return Err(StreamError::InvalidAmount);
+        }
+
+        let to = to.unwrap_or(recipient);
+        Ok(Self::pay_out(&env, stream_id, &mut stream, amount, to, now))
+    }
+
+    /// Withdraw the claimable balance of several streams in one call.
+    ///
+    /// Only the recipient of every listed stream may call this. Claimable
+    /// balances are summed per token and paid with one transfer per token;
+    /// each stream still records its own withdrawal and emits its own
+    /// `tokens_withdrawn` event.
+    ///
+    /// Streams with nothing to withdraw — not started yet, cancelled,
+    /// completed, or simply nothing accrued since the last withdrawal — are
+    /// skipped rather than failing the call. A stream ID listed twice is paid
+    /// once.
+    ///
+    /// Returns one entry per element of `stream_ids`, in order, with the
+    /// amount paid from that stream (0 when skipped).
+    ///
+    /// # Errors
+    /// - `InvalidAmount`  — `stream_ids` is empty.
+    /// - `StreamNotFound` — a listed stream does not exist.
+    /// - `Unauthorized`   — caller is not the recipient of a listed stream.
+    pub fn withdraw_many(
+        env: Env,
+        recipient: Address,
+        stream_ids: Vec<u64>,
+    ) -> Result<Vec<WithdrawalResult>, StreamError> {
+        recipient.require_auth();
+
+        if stream_ids.is_empty() {
+            return Err(StreamError::InvalidAmount);
+        }
+
+        let now = env.ledger().timestamp();
+        let mut totals: Map<Address, i128> = Map::new(&env);
+        let mut results = Vec::new(&env);
+
+        for stream_id in stream_ids.iter() {
+            let claimable = match Self::load_withdrawable(&env, &recipient, stream_id) {
+                Ok(mut stream) => {
+                    let claimable = Self::calculate_claimable(&stream, now);
+                    if claimable > 0 && stream.funding == FundingMode::Allowance {
+                        // Pulled from each sender separately; cannot be pooled.
+                        Self::pay_out(
+                            &env,
+                            stream_id,
+                            &mut stream,
+                            claimable,
+                            recipient.clone(),
+                            now,
+                        )
+                    } else if claimable > 0 {
+                        let total = totals.get(stream.token_address.clone()).unwrap_or(0);
+                        totals.set(stream.token_address.clone(), total + claimable);
+                        Self::record_withdrawal(
+                            &env,
+                            stream_id,
+                            &mut stream,
+                            claimable,
+                            recipient.clone(),
+                            now,
+                        );
+                        claimable
+                    } else {
+                        0
+                    }
+                }
+                Err(StreamError::StreamNotStarted)
+                | Err(StreamError::StreamCancelled)
+                | Err(StreamError::StreamCompleted) => 0,
+                Err(err) => return Err(err),
+            };
+            results.push_back(WithdrawalResult {
+                stream_id,
+                amount: claimable,
+            });
+        }
+
         let contract_address = env.current_contract_address();
-        token_client.transfer(&contract_address, &recipient, &claimable);
+        for (token_address, total) in totals.iter() {
+            let token_client = token::Client::new(&env, &token_address);
+            token_client.transfer(&contract_address, &recipient, &total);
+        }
 
-        stream.withdrawn_amount += claimable;
-        stream.last_update_time = now;
+        Ok(results)
+    }
 
-        // Mark stream as inactive if all funds have been withdrawn
-        if stream.withdrawn_amount >= stream.deposited_amount {
-            stream.is_active = false;
+    /// Cancel a stream that is not yet `Cancelled` or `Completed`.
+    ///
+    /// Only the stream's original sender may cancel. The unaccrued balance is
+    /// returned to the sender; tokens already accrued to the recipient stay in
+    /// the contract and are recorded in `frozen_accrued_amount` for
+    /// `claim_after_cancel`.
+    ///
+    /// # Errors
+    /// - `StreamNotFound`  — no stream exists with `stream_id`.
+    /// - `Unauthorized`    — caller is not the stream's sender.
+    /// - `NotCancelable`   — the stream's policy forbids sender cancellation.
+    /// - `StreamCancelled` — stream has already been cancelled.
+    /// - `StreamCompleted` — stream has been fully withdrawn.
+    pub fn cancel_stream(env: Env, sender: Address, stream_id: u64) -> Result<(), StreamError> {
+        sender.require_auth();
+
+        let mut stream = Self::load_current(&env, stream_id)?;
+
+        if stream.sender != sender {
+            return Err(StreamError::Unauthorized);
+        }
+        if stream.cancelability == Cancelability::NonCancelable {
+            return Err(StreamError::NotCancelable);
+        }
+        Self::ensure_not_terminal(&stream)?;
+
+        Self::settle_cancellation(&env, stream_id, &mut stream, sender, false);
+        Ok(())
+    }
+
+    /// Cancel a stream and settle both parties in the same call.
+    ///
+    /// Behaves like `cancel_stream`, but instead of freezing the recipient's
+    /// accrued balance for `claim_after_cancel`, transfers it to the recipient
+    /// immediately and records it in `withdrawn_amount`. The stream ends fully
+    /// settled with no tokens left in the contract.
+    ///
+    /// # Errors
+    /// - `StreamNotFound`  — no stream exists with `stream_id`.
+    /// - `Unauthorized`    — caller is not the stream's sender.
+    /// - `NotCancelable`   — the stream's policy forbids sender cancellation.
+    /// - `StreamCancelled` — stream has already been cancelled.
+    /// - `StreamCompleted` — stream has been fully withdrawn.
+    pub fn cancel_stream_and_settle(
+        env: Env,
+        sender: Address,
+        stream_id: u64,
+    ) -> Result<(), StreamError> {
+        sender.require_auth();
+
+        let mut stream = Self::load_current(&env, stream_id)?;
+
+        if stream.sender != sender {
+            return Err(StreamError::Unauthorized);
+        }
+        if stream.cancelability == Cancelability::NonCancelable {
+            return Err(StreamError::NotCancelable);
+        }
+        Self::ensure_not_terminal(&stream)?;
+
+        Self::settle_cancellation(&env, stream_id, &mut stream, sender, true);
+        Ok(())
+    }
+
+    /// Cancel a stream from the recipient's side, ending the engagement.
+    ///
+    /// Only the stream's recipient may call this. Tokens accrued so far are
+    /// paid to the recipient immediately and the unaccrued remainder is
+    /// refunded to the sender, leaving nothing to claim afterwards. Requires
+    /// the stream to have been created with `Cancelability::Either`.
+    ///
+    /// # Errors
+    /// - `StreamNotFound`  — no stream exists with `stream_id`.
+    /// - `Unauthorized`    — caller is not the stream's recipient.
+    /// - `NotCancelable`   — the stream's policy forbids recipient cancellation.
+    /// - `StreamCancelled` — stream has already been cancelled.
+    /// - `StreamCompleted` — stream has been fully withdrawn.
+    pub fn cancel_stream_by_recipient(
+        env: Env,
+        recipient: Address,
+        stream_id: u64,
+    ) -> Result<(), StreamError> {
+        recipient.require_auth();
+
+        let mut stream = Self::load_current(&env, stream_id)?;
+
+        if stream.recipient != recipient {
+            return Err(StreamError::Unauthorized);
+        }
+        if stream.cancelability != Cancelability::Either {
+            return Err(StreamError::NotCancelable);
+        }
+        Self::ensure_not_terminal(&stream)?;
+
+        Self::settle_cancellation(&env, stream_id, &mut stream, recipient, true);
+        Ok(())
+    }
+
+    /// Claim the accrued balance frozen when the stream was cancelled.
+    ///
+    /// Only the stream's recipient may call this. The paid amount moves from
+    /// the frozen balance to `withdrawn_amount`.
+    ///
+    /// Returns the amount paid. For an allowance-funded stream whose sender
+    /// is short of funds, this is only the covered part; the rest stays
+    /// claimable.
+    ///
+    /// # Errors
+    /// - `StreamNotFound`  — no stream exists with `stream_id`.
+    /// - `Unauthorized`    — caller is not the stream's recipient.
+    /// - `StreamActive`    — stream has not been cancelled.
+    /// - `InvalidAmount`   — nothing left to claim.
+    pub fn claim_after_cancel(
+        env: Env,
+        recipient: Address,
+        stream_id: u64,
+    ) -> Result<i128, StreamError> {
+        recipient.require_auth();
+
+        let mut stream = load_stream(&env, stream_id)?;
+
+        if stream.recipient != recipient {
+            return Err(StreamError::Unauthorized);
+        }
+        if stream.status != StreamStatus::Cancelled {
+            return Err(StreamError::StreamActive);
+        }
+
+        if stream.frozen_accrued_amount <= 0 {
+            return Err(StreamError::InvalidAmount);
         }
 
+        // An allowance-funded sender may cover only part; the rest stays
+        // claimable.
+        let now = env.ledger().timestamp();
+        let amount = Self::coverable(&env, stream_id, &stream, stream.frozen_accrued_amount, now);
+        if amount > 0 {
+            Self::release(&env, &stream, &recipient, amount);
+        }
+
+        stream.withdrawn_amount += amount;
+        stream.frozen_accrued_amount -= amount;
+        stream.last_update_time = now;
+
         save_stream(&env, stream_id, &stream);
 
         env.events().publish(
-            (Symbol::new(&env, "tokens_withdrawn"), stream_id),
-            TokensWithdrawnEvent {
+            (Symbol::new(&env, "tokens_claimed"), stream_id),
+            TokensClaimedEvent {
                 stream_id,
                 recipient,
-                amount: claimable,
+                amount,
                 timestamp: stream.last_update_time,
             },
         );
 
-        Ok(claimable)
+        Ok(amount)
     }
 
-    /// Cancel an active stream.
+    /// Pause accrual on an active stream.
     ///
-    /// Only the stream's original sender may cancel. Any unspent balance
-    /// (deposited − withdrawn) is returned to the sender.
+    /// Only the stream's sender may pause. Tokens accrued up to now remain
+    /// withdrawable; nothing further accrues until `resume_stream`.
+    /// `NonCancelable` streams cannot be paused, since an indefinite pause
+    /// would withhold the deposit just like a cancellation.
     ///
     /// # Errors
     /// - `StreamNotFound`  — no stream exists with `stream_id`.
     /// - `Unauthorized`    — caller is not the stream's sender.
-    /// - `StreamInactive`  — stream is already inactive.
-    pub fn cancel_stream(env: Env, sender: Address, stream_id: u64) -> Result<(), StreamError> {
+    /// - `NotCancelable`   — the stream is `NonCancelable`.
+    /// - `StreamCancelled` — stream has been cancelled.
+    /// - `StreamCompleted` — stream has been fully withdrawn.
+    /// - `StreamPaused`    — stream is already paused.
+    /// - `StreamEnded`     — stream is `Depleted`; nothing left to pause.
+    pub fn pause_stream(env: Env, sender: Address, stream_id: u64) -> Result<(), StreamError> {
         sender.require_auth();
 
-        let mut stream = load_stream(&env, stream_id)?;
+        let mut stream = Self::load_current(&env, stream_id)?;
 
         if stream.sender != sender {
             return Err(StreamError::Unauthorized);
         }
-        if !stream.is_active {
-            return Err(StreamError::StreamInactive);
+        if stream.cancelability == Cancelability::NonCancelable {
+            return Err(StreamError::NotCancelable);
+        }
+        Self::ensure_not_terminal(&stream)?;
+        match stream.status {
+            StreamStatus::Paused => return Err(StreamError::StreamPaused),
+            StreamStatus::Depleted => return Err(StreamError::StreamEnded),
+            _ => {}
         }
 
-        // Calculate accrued tokens that belong to the recipient
         let now = env.ledger().timestamp();
-        let accrued_amount = Self::calculate_claimable(&stream, now);
 
-        // Refund only the unspent balance minus accrued tokens (accrued tokens stay for recipient)
-        let refunded_amount = stream
-            .deposited_amount
-            .saturating_sub(stream.withdrawn_amount)
-            .saturating_sub(accrued_amount);
+        // Pin the accrued amount so resuming only has to shift the schedule.
+        Self::pin_checkpoint(&mut stream, now);
+        stream.status = StreamStatus::Paused;
+        stream.paused_at = now;
+        stream.last_update_time = now;
 
-        if refunded_amount > 0 {
-            let token_client = token::Client::new(&env, &stream.token_address);
-            let contract_address = env.current_contract_address();
-            token_client.transfer(&contract_address, &sender, &refunded_amount);
+        save_stream(&env, stream_id, &stream);
+
+        env.events().publish(
+            (Symbol::new(&env, "stream_paused"), stream_id),
+            StreamPausedEvent {
+                stream_id,
+                sender,
+                paused_at: now,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Resume a paused stream.
+    ///
+    /// Only the stream's sender may resume. The remaining schedule is shifted
+    /// by the time spent paused, so `end_time` moves out by the same amount.
+    ///
+    /// # Errors
+    /// - `StreamNotFound`  — no stream exists with `stream_id`.
+    /// - `Unauthorized`    — caller is not the stream's sender.
+    /// - `StreamCancelled` — stream has been cancelled.
+    /// - `StreamCompleted` — stream has been fully withdrawn.
+    /// - `StreamNotPaused` — stream is not paused.
+    pub fn resume_stream(env: Env, sender: Address, stream_id: u64) -> Result<(), StreamError> {
+        sender.require_auth();
+
+        let mut stream = Self::load_current(&env, stream_id)?;
+
+        if stream.sender != sender {
+            return Err(StreamError::Unauthorized);
+        }
+        Self::ensure_not_terminal(&stream)?;
+        if stream.status != StreamStatus::Paused {
+            return Err(StreamError::StreamNotPaused);
         }
 
-        stream.is_active = false;
-        stream.last_update_time = now;
+        let now = env.ledger().timestamp();
+        let paused_for = now - stream.paused_at;
 
-        let recipient = stream.recipient.clone();
-        let amount_withdrawn = stream.withdrawn_amount;
+        // A stream paused before it started also has its start pushed back,
+        // and a cliff not yet reached moves with the schedule.
+        if stream.start_time > stream.paused_at {
+            stream.start_time = stream.start_time.saturating_add(paused_for);
+        }
+        if stream.cliff_time > stream.paused_at {
+            stream.cliff_time = stream.cliff_time.saturating_add(paused_for);
+        }
+        stream.checkpoint_time = stream.checkpoint_time.saturating_add(paused_for);
+        stream.end_time = stream.end_time.saturating_add(paused_for);
+        stream.status = StreamStatus::Streaming;
+        Self::sync_status(&mut stream, now);
+        stream.last_update_time = now;
 
         save_stream(&env, stream_id, &stream);
 
         env.events().publish(
-            (Symbol::new(&env, "stream_cancelled"), stream_id),
-            StreamCancelledEvent {
+            (Symbol::new(&env, "stream_resumed"), stream_id),
+            StreamResumedEvent {
                 stream_id,
                 sender,
-                recipient,
-                amount_withdrawn,
-                refunded_amount,
+                resumed_at: now,
+                end_time: stream.end_time,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Reassign the right to future accrual to `new_recipient`.
+    ///
+    /// Only the current recipient may transfer. Anything already accrued is
+    /// paid out to the current recipient first, so the new recipient only
+    /// receives what accrues from now on. For an allowance-funded stream
+    /// whose sender cannot cover that payout in full, the transfer is
+    /// rejected so the unpaid balance stays with the recipient who earned it.
+    ///
+    /// # Errors
+    /// - `StreamNotFound`   — no stream exists with `stream_id`.
+    /// - `Unauthorized`     — caller is not the stream's recipient.
+    /// - `StreamCancelled`  — stream has been cancelled.
+    /// - `StreamCompleted`  — stream has been fully withdrawn.
+    /// - `FundingShortfall` — the sender cannot cover the accrued balance.
+    pub fn transfer_stream(
+        env: Env,
+        recipient: Address,
+        stream_id: u64,
+        new_recipient: Address,
+    ) -> Result<(), StreamError> {
+        recipient.require_auth();
+
+        let mut stream = Self::load_current(&env, stream_id)?;
+
+        if stream.recipient != recipient {
+            return Err(StreamError::Unauthorized);
+        }
+        Self::ensure_not_terminal(&stream)?;
+
+        let now = env.ledger().timestamp();
+        let claimable = Self::calculate_claimable(&stream, now);
+        let settled_amount = if claimable > 0 {
+            Self::pay_out(
+                &env,
+                stream_id,
+                &mut stream,
+                claimable,
+                recipient.clone(),
+                now,
+            )
+        } else {
+            0
+        };
+        if settled_amount < claimable {
+            return Err(StreamError::FundingShortfall);
+        }
+
+        stream.recipient = new_recipient.clone();
+        stream.last_update_time = now;
+
+        save_stream(&env, stream_id, &stream);
+        remove_from_index(&env, StreamIndex::Recipient, &recipient, stream_id);
+        add_to_index(&env, StreamIndex::Recipient, &new_recipient, stream_id);
+
+        env.events().publish(
+            (Symbol::new(&env, "stream_transferred"), stream_id),
+            StreamTransferredEvent {
+                stream_id,
+                from: recipient,
+                to: new_recipient,
+                settled_amount,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Hand control of a stream over to `new_sender`.
+    ///
+    /// Only the current sender may transfer, and `new_sender` must authorize
+    /// taking the stream over: an allowance-funded stream pays out of the
+    /// sender's account. From then on, `new_sender` is the only address that
+    /// can top up, pause, resume or cancel the stream, and receives any
+    /// refund on cancellation.
+    ///
+    /// # Errors
+    /// - `StreamNotFound`  — no stream exists with `stream_id`.
+    /// - `Unauthorized`    — caller is not the stream's sender.
+    /// - `StreamCancelled` — stream has been cancelled.
+    /// - `StreamCompleted` — stream has been fully withdrawn.
+    pub fn transfer_sender(
+        env: Env,
+        sender: Address,
+        stream_id: u64,
+        new_sender: Address,
+    ) -> Result<(), StreamError> {
+        sender.require_auth();
+        if new_sender != sender {
+            new_sender.require_auth();
+        }
+
+        let mut stream = Self::load_current(&env, stream_id)?;
+
+        if stream.sender != sender {
+            return Err(StreamError::Unauthorized);
+        }
+        Self::ensure_not_terminal(&stream)?;
+
+        stream.sender = new_sender.clone();
+        stream.last_update_time = env.ledger().timestamp();
+
+        save_stream(&env, stream_id, &stream);
+        remove_from_index(&env, StreamIndex::Sender, &sender, stream_id);
+        add_to_index(&env, StreamIndex::Sender, &new_sender, stream_id);
+
+        env.events().publish(
+            (Symbol::new(&env, "sender_transferred"), stream_id),
+            SenderTransferredEvent {
+                stream_id,
+                from: sender,
+                to: new_sender,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Delete a settled stream from storage to stop paying rent on it.
+    ///
+    /// Either the sender or the recipient may close a stream once it is
+    /// `Completed`, or `Cancelled` with nothing left for the recipient to
+    /// claim. The stream is removed from storage and from both address
+    /// indexes; `get_stream` returns `None` afterwards and its ID is never
+    /// reused. A `stream_closed` event records the final state for indexers.
+    ///
+    /// # Errors
+    /// - `StreamNotFound`   — no stream exists with `stream_id`.
+    /// - `Unauthorized`     — caller is neither the sender nor the recipient.
+    /// - `StreamActive`     — stream is neither cancelled nor completed.
+    /// - `BalanceRemaining` — cancelled stream still has unclaimed tokens.
+    pub fn close_stream(env: Env, caller: Address, stream_id: u64) -> Result<(), StreamError> {
+        caller.require_auth();
+
+        let stream = Self::load_current(&env, stream_id)?;
+
+        if caller != stream.sender && caller != stream.recipient {
+            return Err(StreamError::Unauthorized);
+        }
+        match stream.status {
+            StreamStatus::Cancelled | StreamStatus::Completed => {}
+            _ => return Err(StreamError::StreamActive),
+        }
+        if stream.withdrawn_amount < stream.deposited_amount || stream.frozen_accrued_amount > 0 {
+            return Err(StreamError::BalanceRemaining);
+        }
+
+        remove_stream(&env, stream_id);
+        remove_from_index(&env, StreamIndex::Sender, &stream.sender, stream_id);
+        remove_from_index(&env, StreamIndex::Recipient, &stream.recipient, stream_id);
+
+        env.events().publish(
+            (Symbol::new(&env, "stream_closed"), stream_id),
+            StreamClosedEvent {
+                stream_id,
+                sender: stream.sender,
+                recipient: stream.recipient,
+                closed_by: caller,
+                total_withdrawn: stream.withdrawn_amount,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Extend the storage TTL of a stream so it is not archived.
+    ///
+    /// Anyone may call this (e.g. a recipient of a long-running stream, or a
+    /// keeper bot); it only pays rent and changes no stream state. Streams
+    /// are also extended automatically whenever they are read or written.
+    ///
+    /// # Errors
+    /// - `StreamNotFound` — no stream exists with `stream_id`.
+    pub fn bump_stream(env: Env, stream_id: u64) -> Result<(), StreamError> {
+        extend_stream_ttl(&env, stream_id)
+    }
+
+    // ─── Subscriptions ────────────────────────────────────────────────────────
+
+    /// Subscribe to pay `merchant` a fixed `amount_per_period` every `period`
+    /// seconds, for `max_periods` periods.
+    ///
+    /// Nothing is escrowed. Instead, the subscriber must approve this
+    /// contract to spend at least the amount due (e.g. via the token's
+    /// `approve`); each period is then pulled by `charge_subscription`. The
+    /// first period is due immediately.
+    ///
+    /// Returns the new subscription ID.
+    ///
+    /// # Errors
+    /// - `InvalidAmount`   — `amount_per_period` ≤ 0 or `max_periods` is 0.
+    /// - `InvalidDuration` — `period` is 0.
+    pub fn subscribe(
+        env: Env,
+        subscriber: Address,
+        merchant: Address,
+        token_address: Address,
+        amount_per_period: i128,
+        period: u64,
+        max_periods: u32,
+    ) -> Result<u64, StreamError> {
+        subscriber.require_auth();
+
+        if amount_per_period <= 0 || max_periods == 0 {
+            return Err(StreamError::InvalidAmount);
+        }
+        if period == 0 {
+            return Err(StreamError::InvalidDuration);
+        }
+
+        let subscription_id = next_subscription_id(&env);
+        let start_time = env.ledger().timestamp();
+        let subscription = Subscription {
+            subscriber: subscriber.clone(),
+            merchant: merchant.clone(),
+            token_address: token_address.clone(),
+            amount_per_period,
+            period,
+            max_periods,
+            periods_charged: 0,
+            start_time,
+            status: SubscriptionStatus::Active,
+        };
+        save_subscription(&env, subscription_id, &subscription);
+
+        env.events().publish(
+            (Symbol::new(&env, "subscription_created"), subscription_id),
+            SubscriptionCreatedEvent {
+                subscription_id,
+                subscriber,
+                merchant,
+                token_address,
+                amount_per_period,
+                period,
+                max_periods,
+                start_time,
+            },
+        );
+
+        Ok(subscription_id)
+    }
+
+    /// Charge the next due period of a subscription.
+    ///
+    /// Anyone may call this — typically the merchant or a keeper bot; funds
+    /// always move from the subscriber to the merchant. Each call charges
+    /// exactly one period, so periods missed in the past can be caught up
+    /// with repeated calls. The subscription becomes `Completed` after its
+    /// last period.
+    ///
+    /// Fails (and charges nothing) if the subscriber's allowance or balance
+    /// does not cover `amount_per_period`.
+    ///
+    /// Returns the amount charged.
+    ///
+    /// # Errors
+    /// - `SubscriptionNotFound` — no subscription exists with `subscription_id`.
+    /// - `StreamCancelled`      — subscription has been cancelled.
+    /// - `StreamCompleted`      — all periods have been charged.
+    /// - `PaymentNotDue`        — the next period is not due yet.
+    pub fn charge_subscription(env: Env, subscription_id: u64) -> Result<i128, StreamError> {
+        let mut subscription = load_subscription(&env, subscription_id)?;
+        Self::ensure_subscription_active(&subscription)?;
+
+        let now = env.ledger().timestamp();
+        let due_at = (subscription.periods_charged as u64)
+            .checked_mul(subscription.period)
+            .and_then(|offset| subscription.start_time.checked_add(offset))
+            .unwrap_or(u64::MAX);
+        if now < due_at {
+            return Err(StreamError::PaymentNotDue);
+        }
+
+        let amount = subscription.amount_per_period;
+        let token_client = token::Client::new(&env, &subscription.token_address);
+        token_client.transfer_from(
+            &env.current_contract_address(),
+            &subscription.subscriber,
+            &subscription.merchant,
+            &amount,
+        );
+
+        subscription.periods_charged += 1;
+        if subscription.periods_charged >= subscription.max_periods {
+            subscription.status = SubscriptionStatus::Completed;
+        }
+        save_subscription(&env, subscription_id, &subscription);
+
+        env.events().publish(
+            (Symbol::new(&env, "subscription_charged"), subscription_id),
+            SubscriptionChargedEvent {
+                subscription_id,
+                subscriber: subscription.subscriber,
+                merchant: subscription.merchant,
+                amount,
+                periods_charged: subscription.periods_charged,
+                timestamp: now,
+            },
+        );
+
+        Ok(amount)
+    }
+
+    /// Cancel a subscription so no further periods can be charged.
+    ///
+    /// Either the subscriber or the merchant may cancel. Periods already
+    /// charged are not refunded.
+    ///
+    /// # Errors
+    /// - `SubscriptionNotFound` — no subscription exists with `subscription_id`.
+    /// - `Unauthorized`         — caller is neither the subscriber nor the merchant.
+    /// - `StreamCancelled`      — subscription has already been cancelled.
+    /// - `StreamCompleted`      — all periods have been charged.
+    pub fn cancel_subscription(
+        env: Env,
+        caller: Address,
+        subscription_id: u64,
+    ) -> Result<(), StreamError> {
+        caller.require_auth();
+
+        let mut subscription = load_subscription(&env, subscription_id)?;
+
+        if caller != subscription.subscriber && caller != subscription.merchant {
+            return Err(StreamError::Unauthorized);
+        }
+        Self::ensure_subscription_active(&subscription)?;
+
+        subscription.status = SubscriptionStatus::Cancelled;
+        save_subscription(&env, subscription_id, &subscription);
+
+        env.events().publish(
+            (Symbol::new(&env, "subscription_cancelled"), subscription_id),
+            SubscriptionCancelledEvent {
+                subscription_id,
+                subscriber: subscription.subscriber,
+                merchant: subscription.merchant,
+                cancelled_by: caller,
+                periods_charged: subscription.periods_charged,
             },
         );
 
@@ -366,37 +1562,580 @@ impl StreamContract {
     // ─── Read-only Queries ────────────────────────────────────────────────────
 
     /// Returns the stream record for `stream_id`, or `None` if it does not exist.
+    ///
+    /// The returned `status` reflects the current ledger time, even if no
+    /// entrypoint has touched the stream since it started or ran out.
     pub fn get_stream(env: Env, stream_id: u64) -> Option<Stream> {
-        try_load_stream(&env, stream_id)
+        let mut stream = try_load_stream(&env, stream_id)?;
+        Self::sync_status(&mut stream, env.ledger().timestamp());
+        Some(stream)
+    }
+
+    /// Amount the recipient could withdraw right now.
+    ///
+    /// For a cancelled stream this is the accrued balance still waiting for
+    /// `claim_after_cancel`.
+    ///
+    /// # Errors
+    /// - `StreamNotFound` — no stream exists with `stream_id`.
+    pub fn get_claimable(env: Env, stream_id: u64) -> Result<i128, StreamError> {
+        let stream = Self::load_current(&env, stream_id)?;
+        Ok(Self::balance_at(&stream, env.ledger().timestamp()).claimable)
+    }
+
+    /// Amount the sender would get back if the stream were cancelled right
+    /// now. Always 0 once the stream is `Cancelled` or `Completed`, and for
+    /// allowance-funded streams, which never hold the sender's tokens.
+    ///
+    /// This does not check whether the stream's cancelability policy
+    /// actually allows cancellation.
+    ///
+    /// # Errors
+    /// - `StreamNotFound` — no stream exists with `stream_id`.
+    pub fn get_refundable(env: Env, stream_id: u64) -> Result<i128, StreamError> {
+        let stream = Self::load_current(&env, stream_id)?;
+        Ok(Self::balance_at(&stream, env.ledger().timestamp()).refundable)
+    }
+
+    /// Breakdown of a stream's balance at timestamp `at`, or now when `at`
+    /// is `None`.
+    ///
+    /// A future `at` projects the current schedule forward, assuming no
+    /// further top-ups, pauses or cancellation; a paused stream stays frozen
+    /// at its pause time. Uses the same accrual math as `withdraw`. Past
+    /// balances are not reconstructed: the stored state only describes the
+    /// schedule from its last checkpoint on.
+    ///
+    /// # Errors
+    /// - `StreamNotFound`   — no stream exists with `stream_id`.
+    /// - `InvalidTimestamp` — `at` is before the current ledger time.
+    pub fn get_stream_balance(
+        env: Env,
+        stream_id: u64,
+        at: Option<u64>,
+    ) -> Result<StreamBalance, StreamError> {
+        let now = env.ledger().timestamp();
+        let at = at.unwrap_or(now);
+        if at < now {
+            return Err(StreamError::InvalidTimestamp);
+        }
+        let stream = Self::load_current(&env, stream_id)?;
+        Ok(Self::balance_at(&stream, at))
+    }
+
+    /// Returns the subscription record for `subscription_id`, or `None` if it
+    /// does not exist.
+    pub fn get_subscription(env: Env, subscription_id: u64) -> Option<Subscription> {
+        try_load_subscription(&env, subscription_id)
+    }
+
+    /// Returns up to `limit` IDs of streams currently sent by `sender`,
+    /// skipping the first `start`.
+    ///
+    /// IDs are listed in the order the streams were created or handed over
+    /// to `sender` via `transfer_sender`, except that when a stream leaves
+    /// the list the most recently added ID takes its place. `limit` is
+    /// capped at `MAX_PAGE_SIZE`.
+    pub fn get_streams_by_sender(env: Env, sender: Address, start: u32, limit: u32) -> Vec<u64> {
+        load_index(
+            &env,
+            StreamIndex::Sender,
+            &sender,
+            start,
+            limit.min(MAX_PAGE_SIZE),
+        )
+    }
+
+    /// Returns up to `limit` IDs of streams currently paying `recipient`,
+    /// skipping the first `start`.
+    ///
+    /// IDs are listed in the order the streams were created or handed over
+    /// to `recipient` via `transfer_stream`, except that when a stream leaves
+    /// the list the most recently added ID takes its place. `limit` is
+    /// capped at `MAX_PAGE_SIZE`.
+    pub fn get_streams_by_recipient(
+        env: Env,
+        recipient: Address,
+        start: u32,
+        limit: u32,
+    ) -> Vec<u64> {
+        load_index(
+            &env,
+            StreamIndex::Recipient,
+            &recipient,
+            start,
+            limit.min(MAX_PAGE_SIZE),
+        )
     }
 
     // ─── Internal Helpers ─────────────────────────────────────────────────────
 
-    /// Deducts the protocol fee from `amount`, transfers it to the treasury,
-    /// emits a `fee_collected` event, and returns the net amount.
+    /// Validates `params` against the current ledger time and returns the
+    /// resolved `(start_time, end_time, cliff_time)`.
+    ///
+    /// A missing `start_time` means "now"; a missing `cliff_time` means no
+    /// cliff (it defaults to the start).
+    fn resolve_schedule(env: &Env, params: &StreamParams) -> Result<(u64, u64, u64), StreamError> {
+        if params.amount <= 0 {
+            return Err(StreamError::InvalidAmount);
+        }
+        if params.duration == 0 {
+            return Err(StreamError::InvalidDuration);
+        }
+
+        let now = env.ledger().timestamp();
+        let start_time = match params.start_time {
+            Some(start_time) if start_time < now => return Err(StreamError::InvalidStartTime),
+            Some(start_time) => start_time,
+            None => now,
+        };
+
+        let end_time = start_time.saturating_add(params.duration);
+        let cliff_time = params.cliff_time.unwrap_or(start_time);
+        if cliff_time < start_time || cliff_time > end_time {
+            return Err(StreamError::InvalidCliff);
+        }
+
+        Ok((start_time, end_time, cliff_time))
+    }
+
+    /// Validates the schedule, funds `amount` from `sender` per `funding`,
+    /// deducts the protocol fee, and records the new stream.
+    ///
+    /// Shared by the single-stream creation entrypoints; callers handle auth.
+    fn open_stream(
+        env: &Env,
+        sender: Address,
+        token_address: Address,
+        params: StreamParams,
+        funding: FundingMode,
+    ) -> Result<u64, StreamError> {
+        Self::ensure_protocol_active(env)?;
+        let schedule = Self::resolve_schedule(env, &params)?;
+        let stream_id = next_stream_id(env);
+
+        // Net amount (== amount when no fee config).
+        let net_amount = Self::fund(
+            env,
+            &sender,
+            &token_address,
+            params.amount,
+            funding,
+            stream_id,
+        );
+
+        Self::record_stream(
+            env,
+            stream_id,
+            sender,
+            token_address,
+            params,
+            schedule,
+            net_amount,
+            funding,
+        );
+        Ok(stream_id)
+    }
+
+    /// Persists a funded stream and emits `stream_created`.
+    ///
+    /// `schedule` is the `(start_time, end_time, cliff_time)` returned by
+    /// `resolve_schedule`; `net_amount` is the deposit after protocol fees.
+    fn record_stream(
+        env: &Env,
+        stream_id: u64,
+        sender: Address,
+        token_address: Address,
+        params: StreamParams,
+        schedule: (u64, u64, u64),
+        net_amount: i128,
+        funding: FundingMode,
+    ) {
+        let (start_time, end_time, cliff_time) = schedule;
+        let rate_per_second = net_amount / (params.duration as i128);
+
+        let now = env.ledger().timestamp();
+        let mut stream = Stream {
+            sender: sender.clone(),
+            recipient: params.recipient.clone(),
+            token_address: token_address.clone(),
+            rate_per_second,
+            deposited_amount: net_amount,
+            withdrawn_amount: 0,
+            start_time,
+            end_time,
+            cliff_time,
+            cancelability: params.cancelability,
+            funding,
+            checkpoint_time: start_time,
+            checkpoint_streamed: 0,
+            last_update_time: now,
+            status: StreamStatus::Scheduled,
+            paused_at: 0,
+            frozen_accrued_amount: 0,
+        };
+        Self::sync_status(&mut stream, now);
+        save_stream(env, stream_id, &stream);
+        add_to_index(env, StreamIndex::Sender, &sender, stream_id);
+        add_to_index(env, StreamIndex::Recipient, &params.recipient, stream_id);
+
+        env.events().publish(
+            (Symbol::new(env, "stream_created"), stream_id),
+            StreamCreatedEvent {
+                stream_id,
+                sender,
+                recipient: params.recipient,
+                rate_per_second,
+                token_address,
+                deposited_amount: net_amount,
+                start_time,
+                end_time,
+                cliff_time,
+                cancelability: params.cancelability,
+            },
+        );
+    }
+
+    /// Marks `stream` as `Cancelled`, refunds the unaccrued balance to the
+    /// sender, and emits `stream_cancelled`.
+    ///
+    /// With `pay_recipient`, the accrued balance is transferred to the
+    /// recipient in the same call; otherwise it is frozen in
+    /// `frozen_accrued_amount` for `claim_after_cancel`.
+    fn settle_cancellation(
+        env: &Env,
+        stream_id: u64,
+        stream: &mut Stream,
+        cancelled_by: Address,
+        pay_recipient: bool,
+    ) {
+        // Calculate accrued tokens that belong to the recipient
+        let now = env.ledger().timestamp();
+        let accrued_amount = Self::calculate_claimable(stream, now);
+
+        // Refund only the unspent balance minus accrued tokens
+        let refunded_amount = stream
+            .deposited_amount
+            .saturating_sub(stream.withdrawn_amount)
+            .saturating_sub(accrued_amount);
+
+        // Allowance-funded streams never escrowed the unaccrued balance, so
+        // there is nothing to send back.
+        if refunded_amount > 0 && stream.funding == FundingMode::Escrowed {
+            let token_client = token::Client::new(env, &stream.token_address);
+            token_client.transfer(
+                &env.current_contract_address(),
+                &stream.sender,
+                &refunded_amount,
+            );
+        }
+
+        let recipient_payout = if pay_recipient {
+            // Anything an allowance-funded sender cannot cover right now
+            // stays claimable via `claim_after_cancel`.
+            let paid = Self::coverable(env, stream_id, stream, accrued_amount, now);
+            if paid > 0 {
+                let recipient = stream.recipient.clone();
+                Self::release(env, stream, &recipient, paid);
+            }
+            stream.withdrawn_amount += paid;
+            stream.frozen_accrued_amount = accrued_amount - paid;
+            paid
+        } else {
+            stream.frozen_accrued_amount = accrued_amount;
+            0
+        };
+
+        // Truncate the schedule at cancellation so it streams exactly what the
+        // recipient is owed.
+        stream.status = StreamStatus::Cancelled;
+        stream.deposited_amount -= refunded_amount;
+        stream.end_time = stream.end_time.min(now);
+        stream.last_update_time = now;
+
+        save_stream(env, stream_id, stream);
+
+        env.events().publish(
+            (Symbol::new(env, "stream_cancelled"), stream_id),
+            StreamCancelledEvent {
+                stream_id,
+                sender: stream.sender.clone(),
+                recipient: stream.recipient.clone(),
+                cancelled_by,
+                amount_withdrawn: stream.withdrawn_amount,
+                recipient_payout,
+                refunded_amount,
+            },
+        );
+    }
+
+    /// Loads a stream and brings its time-driven status up to date.
+    fn load_current(env: &Env, stream_id: u64) -> Result<Stream, StreamError> {
+        let mut stream = load_stream(env, stream_id)?;
+        Self::sync_status(&mut stream, env.ledger().timestamp());
+        Ok(stream)
+    }
+
+    /// Applies the time-driven transitions between `Scheduled`, `Streaming`
+    /// and `Depleted` (a top-up can move a `Depleted` stream back to
+    /// `Streaming`). `Paused`, `Cancelled` and `Completed` only change through
+    /// explicit entrypoints and are left untouched.
+    fn sync_status(stream: &mut Stream, now: u64) {
+        if matches!(
+            stream.status,
+            StreamStatus::Scheduled | StreamStatus::Streaming | StreamStatus::Depleted
+        ) {
+            stream.status = if now < stream.start_time {
+                StreamStatus::Scheduled
+            } else if now >= stream.end_time {
+                StreamStatus::Depleted
+            } else {
+                StreamStatus::Streaming
+            };
+        }
+    }
+
+    /// Rejects streams in a terminal status with a status-specific error.
+    fn ensure_not_terminal(stream: &Stream) -> Result<(), StreamError> {
+        match stream.status {
+            StreamStatus::Cancelled => Err(StreamError::StreamCancelled),
+            StreamStatus::Completed => Err(StreamError::StreamCompleted),
+            _ => Ok(()),
+        }
+    }
+
+    /// Loads a stream and checks that `recipient` may withdraw from it.
+    fn load_withdrawable(
+        env: &Env,
+        recipient: &Address,
+        stream_id: u64,
+    ) -> Result<Stream, StreamError> {
+        let stream = Self::load_current(env, stream_id)?;
+
+        if stream.recipient != *recipient {
+            return Err(StreamError::Unauthorized);
+        }
+        Self::ensure_not_terminal(&stream)?;
+        if stream.status == StreamStatus::Scheduled {
+            return Err(StreamError::StreamNotStarted);
+        }
+        Ok(stream)
+    }
+
+    /// Transfers `amount` of the stream's token to `to` and records the
+    /// withdrawal via `record_withdrawal`.
+    ///
+    /// For allowance-funded streams the sender may not cover `amount`; see
+    /// `coverable`. On a shortfall, accrual stops at `now`: the schedule is
+    /// truncated to what has accrued so far (the recipient stays owed all of
+    /// it) and the stream is marked `Depleted`. Returns the amount actually
+    /// paid.
+    fn pay_out(
+        env: &Env,
+        stream_id: u64,
+        stream: &mut Stream,
+        amount: i128,
+        to: Address,
+        now: u64,
+    ) -> i128 {
+        let covered = Self::coverable(env, stream_id, stream, amount, now);
+        if covered < amount {
+            stream.deposited_amount = Self::streamed_at(stream, now);
+            stream.end_time = now;
+            stream.status = StreamStatus::Depleted;
+            stream.last_update_time = now;
+        }
+        let amount = covered;
+
+        if amount > 0 {
+            Self::release(env, stream, &to, amount);
+            Self::record_withdrawal(env, stream_id, stream, amount, to, now);
+        } else {
+            save_stream(env, stream_id, stream);
+        }
+        amount
+    }
+
+    /// Sends `amount` of the stream's token to `to`: from escrow, or from the
+    /// sender via `transfer_from` for allowance-funded streams.
+    fn release(env: &Env, stream: &Stream, to: &Address, amount: i128) {
+        let token_client = token::Client::new(env, &stream.token_address);
+        let contract_address = env.current_contract_address();
+        match stream.funding {
+            FundingMode::Escrowed => token_client.transfer(&contract_address, to, &amount),
+            FundingMode::Allowance => {
+                token_client.transfer_from(&contract_address, &stream.sender, to, &amount)
+            }
+        }
+    }
+
+    /// Returns how much of `amount` can be paid out right now.
+    ///
+    /// Escrowed streams always cover it. Allowance-funded streams are limited
+    /// by both the sender's allowance and balance; when those fall short, a
+    /// `funding_shortfall` event is emitted and the available amount is
+    /// returned. Callers decide what happens to the uncovered rest.
+    fn coverable(env: &Env, stream_id: u64, stream: &Stream, amount: i128, now: u64) -> i128 {
+        if stream.funding == FundingMode::Escrowed {
+            return amount;
+        }
+
+        let token_client = token::Client::new(env, &stream.token_address);
+        let allowance = token_client.allowance(&stream.sender, &env.current_contract_address());
+        let available = allowance.min(token_client.balance(&stream.sender)).max(0);
+        if available >= amount {
+            return amount;
+        }
+
+        env.events().publish(
+            (Symbol::new(env, "funding_shortfall"), stream_id),
+            FundingShortfallEvent {
+                stream_id,
+                sender: stream.sender.clone(),
+                requested: amount,
+                available,
+                timestamp: now,
+            },
+        );
+
+        available
+    }
+
+    /// Records a withdrawal of `amount` already paid (or about to be paid) to
+    /// `to`: updates `withdrawn_amount`, marks the stream `Completed` once
+    /// fully drained, saves it, and emits a `tokens_withdrawn` event.
+    fn record_withdrawal(
+        env: &Env,
+        stream_id: u64,
+        stream: &mut Stream,
+        amount: i128,
+        to: Address,
+        now: u64,
+    ) {
+        stream.withdrawn_amount += amount;
+        stream.last_update_time = now;
+
+        // Mark stream as completed if all funds have been withdrawn
+        if stream.withdrawn_amount >= stream.deposited_amount {
+            stream.status = StreamStatus::Completed;
+        }
+
+        save_stream(env, stream_id, stream);
+
+        env.events().publish(
+            (Symbol::new(env, "tokens_withdrawn"), stream_id),
+            TokensWithdrawnEvent {
+                stream_id,
+                recipient: stream.recipient.clone(),
+                to,
+                amount,
+                timestamp: now,
+            },
+        );
+    }
+
+    /// Funds a create or top-up of `amount` and returns the net amount after
+    /// the protocol fee.
+    ///
+    /// Escrowed streams pull `amount` into the contract and pay the fee from
+    /// escrow. Allowance-funded streams escrow nothing; the fee is paid
+    /// straight from `sender`.
+    fn fund(
+        env: &Env,
+        sender: &Address,
+        token_address: &Address,
+        amount: i128,
+        funding: FundingMode,
+        stream_id: u64,
+    ) -> i128 {
+        let fee_payer = match funding {
+            FundingMode::Escrowed => {
+                let contract_address = env.current_contract_address();
+                let token_client = token::Client::new(env, token_address);
+                token_client.transfer(sender, &contract_address, &amount);
+                contract_address
+            }
+            FundingMode::Allowance => sender.clone(),
+        };
+        Self::collect_fee(env, &fee_payer, token_address, amount, stream_id)
+    }
+
+    /// Deducts the protocol fee from `amount`, transfers it from `from` to the
+    /// treasury, emits a `fee_collected` event, and returns the net amount.
     ///
     /// If no protocol config exists or the fee rate is 0, returns `amount` unchanged.
     /// Time complexity: O(1).
-    fn collect_fee(env: &Env, token_address: &Address, amount: i128, stream_id: u64) -> i128 {
+    fn collect_fee(
+        env: &Env,
+        from: &Address,
+        token_address: &Address,
+        amount: i128,
+        stream_id: u64,
+    ) -> i128 {
         match try_load_config(env) {
             Some(cfg) if cfg.fee_rate_bps > 0 => {
-                let fee = amount * (cfg.fee_rate_bps as i128) / 10_000;
+                let fee = Self::fee_for(&cfg, amount);
                 if fee > 0 {
                     let token_client = token::Client::new(env, token_address);
-                    token_client.transfer(&env.current_contract_address(), &cfg.treasury, &fee);
-                    env.events().publish(
-                        (Symbol::new(env, "fee_collected"), stream_id),
-                        FeeCollectedEvent {
-                            stream_id,
-                            treasury: cfg.treasury,
-                            fee_amount: fee,
-                            token: token_address.clone(),
-                        },
-                    );
+                    token_client.transfer(from, &cfg.treasury, &fee);
+                    Self::emit_fee_collected(env, &cfg, token_address, fee, stream_id);
                 }
                 amount - fee
             }
             _ => amount,
         }
     }
+
+    /// Rejects subscriptions that are `Cancelled` or `Completed`.
+    fn ensure_subscription_active(subscription: &Subscription) -> Result<(), StreamError> {
+        match subscription.status {
+            SubscriptionStatus::Active => Ok(()),
+            SubscriptionStatus::Cancelled => Err(StreamError::StreamCancelled),
+            SubscriptionStatus::Completed => Err(StreamError::StreamCompleted),
+        }
+    }
+
+    /// Rejects new deposits while the protocol is paused by `pause_protocol`.
+    fn ensure_protocol_active(env: &Env) -> Result<(), StreamError> {
+        match try_load_config(env) {
+            Some(cfg) if cfg.paused => Err(StreamError::ProtocolPaused),
+            _ => Ok(()),
+        }
+    }
+
+    /// Requires `admin`'s auth and checks it is the current protocol admin.
+    /// Returns the loaded config.
+    fn require_admin(env: &Env, admin: &Address) -> Result<ProtocolConfig, StreamError> {
+        admin.require_auth();
+
+        let config = load_config(env)?;
+        if config.admin.as_ref() != Some(admin) {
+            return Err(StreamError::NotAdmin);
+        }
+        Ok(config)
+    }
+
+    /// Protocol fee owed on a gross `amount` under `cfg`, rounded down.
+    fn fee_for(cfg: &ProtocolConfig, amount: i128) -> i128 {
+        amount * (cfg.fee_rate_bps as i128) / 10_000
+    }
+
+    /// Emits a `fee_collected` event for `stream_id`.
+    fn emit_fee_collected(
+        env: &Env,
+        cfg: &ProtocolConfig,
+        token_address: &Address,
+        fee: i128,
+        stream_id: u64,
+    ) {
+        env.events().publish(
+            (Symbol::new(env, "fee_collected"), stream_id),
+            FeeCollectedEvent {
+                stream_id,
+                treasury: cfg.treasury.clone(),
+                fee_amount: fee,
+                token: token_address.clone(),
+            },
+        );
+    }
 }
@@ -1,7 +1,157 @@
-use soroban_sdk::Env;
+use soroban_sdk::{Address, Env, Map, Symbol, TryFromVal, Val, Vec};
 
 use crate::errors::StreamError;
-use crate::types::{DataKey, ProtocolConfig, Stream};
+use crate::types::{
+    Cancelability, DataKey, FundingMode, ProtocolConfig, ProtocolConfigV1, Stream, StreamStatus,
+    StreamV0, Subscription, VersionedStream,
+};
+
+// ─── TTL Policy ──────────────────────────────────────────────────────────────
+
+/// Ledgers per day at a ~5 second close time.
+pub const DAY_IN_LEDGERS: u32 = 17_280;
+
+/// Contract instance (counter, config) is extended to live this many ledgers…
+pub const INSTANCE_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+/// …whenever its remaining TTL drops below this.
+pub const INSTANCE_LIFETIME_THRESHOLD: u32 = INSTANCE_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+/// Persistent entries (streams, address indexes) are extended to live this
+/// many ledgers…
+pub const PERSISTENT_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+/// …whenever their remaining TTL drops below this.
+pub const PERSISTENT_LIFETIME_THRESHOLD: u32 = PERSISTENT_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+/// Extends the contract instance TTL per the policy above.
+///
+/// Called on every storage access so that any interaction keeps the
+/// contract alive.
+pub fn bump_instance(env: &Env) {
+    env.storage()
+        .instance()
+        .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+}
+
+/// Extends the TTL of the persistent entry at `key` per the policy above.
+///
+/// The entry must exist.
+fn bump_persistent(env: &Env, key: &DataKey) {
+    env.storage().persistent().extend_ttl(
+        key,
+        PERSISTENT_LIFETIME_THRESHOLD,
+        PERSISTENT_BUMP_AMOUNT,
+    );
+}
+
+// ─── Schema Version ──────────────────────────────────────────────────────────
+
+/// Storage schema version written by this build of the contract.
+///
+/// Bump this, and extend `migrate_stream` / `migrate_instance`, whenever the
+/// layout of a stored type changes.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Returns the schema version the instance storage was last migrated to.
+///
+/// 0 means the instance predates schema versioning and has never been
+/// initialized or migrated.
+pub fn load_schema_version(env: &Env) -> u32 {
+    bump_instance(env);
+    env.storage()
+        .instance()
+        .get(&DataKey::SchemaVersion)
+        .unwrap_or(0)
+}
+
+/// Records that instance storage is at schema `version`.
+pub fn save_schema_version(env: &Env, version: u32) {
+    env.storage()
+        .instance()
+        .set(&DataKey::SchemaVersion, &version);
+    bump_instance(env);
+}
+
+/// Applies one-off instance-storage migrations from schema `from` to
+/// `SCHEMA_VERSION`. Called by the `migrate` entrypoint after an upgrade.
+///
+/// Persistent records are not touched here; they migrate lazily on load
+/// (see `migrate_stream`).
+pub fn migrate_instance(env: &Env, from: u32) {
+    // 0 → 1: versioning starts here; there is nothing to rewrite.
+
+    // 1 → 2: `ProtocolConfig` gained `paused`.
+    if from < 2 {
+        let old: Option<ProtocolConfigV1> = env.storage().instance().get(&DataKey::ProtocolConfig);
+        if let Some(old) = old {
+            save_config(
+                env,
+                &ProtocolConfig {
+                    admin: old.admin,
+                    treasury: old.treasury,
+                    fee_rate_bps: old.fee_rate_bps,
+                    paused: false,
+                },
+            );
+        }
+    }
+}
+
+/// Upgrades a stored stream record to the current `Stream` layout.
+///
+/// Records keep the variant they were written with until the stream is next
+/// saved, so a layout change only needs a new `VersionedStream` variant and
+/// a conversion arm here — no bulk rewrite of every stream.
+fn migrate_stream(stored: VersionedStream) -> Stream {
+    match stored {
+        VersionedStream::V0(old) => migrate_stream_v0(old),
+        VersionedStream::V1(stream) => stream,
+    }
+}
+
+/// Converts a pre-versioning record into an equivalent schedule.
+///
+/// V0 accrued `rate_per_second` from `last_update_time` on top of what was
+/// already withdrawn, so that pair becomes the checkpoint and the schedule
+/// ends once the unwithdrawn balance has streamed; a zero rate never
+/// finished. An inactive record was either drained or cancelled. V0
+/// cancellation did not record the accrued balance it kept back, so none
+/// is left claimable.
+fn migrate_stream_v0(old: StreamV0) -> Stream {
+    let remaining = old.deposited_amount - old.withdrawn_amount;
+    let end_time = if old.rate_per_second > 0 {
+        let duration = (remaining + old.rate_per_second - 1) / old.rate_per_second;
+        old.last_update_time
+            .saturating_add(u64::try_from(duration).unwrap_or(u64::MAX))
+    } else {
+        u64::MAX
+    };
+    let status = if old.is_active {
+        StreamStatus::Streaming
+    } else if remaining <= 0 {
+        StreamStatus::Completed
+    } else {
+        StreamStatus::Cancelled
+    };
+    Stream {
+        sender: old.sender,
+        recipient: old.recipient,
+        token_address: old.token_address,
+        rate_per_second: old.rate_per_second,
+        deposited_amount: old.deposited_amount,
+        withdrawn_amount: old.withdrawn_amount,
+        start_time: old.start_time,
+        end_time,
+        cliff_time: old.start_time,
+        cancelability: Cancelability::SenderOnly,
+        funding: FundingMode::Escrowed,
+        checkpoint_time: old.last_update_time,
+        checkpoint_streamed: old.withdrawn_amount,
+        last_update_time: old.last_update_time,
+        status,
+        paused_at: 0,
+        frozen_accrued_amount: 0,
+    }
+}
 
 // ─── Stream Counter ───────────────────────────────────────────────────────────
 
@@ -17,20 +167,18 @@ pub fn next_stream_id(env: &Env) -> u64 {
         .unwrap_or(0)
         + 1;
     env.storage().instance().set(&DataKey::StreamCounter, &id);
+    bump_instance(env);
     id
 }
 
 // ─── Stream CRUD ─────────────────────────────────────────────────────────────
 
-/// Loads a stream by ID from persistent storage.
+/// Loads a stream by ID from persistent storage, extending its TTL.
 ///
 /// Returns `StreamNotFound` if no entry exists, keeping error handling
 /// central and preventing duplicated `match storage.get(...)` patterns.
 pub fn load_stream(env: &Env, stream_id: u64) -> Result<Stream, StreamError> {
-    env.storage()
-        .persistent()
-        .get(&DataKey::Stream(stream_id))
-        .ok_or(StreamError::StreamNotFound)
+    try_load_stream(env, stream_id).ok_or(StreamError::StreamNotFound)
 }
 
 /// Persists a stream record in persistent storage.
@@ -38,14 +186,285 @@ pub fn load_stream(env: &Env, stream_id: u64) -> Result<Stream, StreamError> {
 /// Always use this instead of calling `.set` directly so that the key
 /// strategy remains the single source of truth.
 pub fn save_stream(env: &Env, stream_id: u64, stream: &Stream) {
+    let key = DataKey::Stream(stream_id);
+    env.storage()
+        .persistent()
+        .set(&key, &VersionedStream::V1(stream.clone()));
+    bump_persistent(env, &key);
+    bump_instance(env);
+}
+
+/// Deletes a stream record from persistent storage.
+pub fn remove_stream(env: &Env, stream_id: u64) {
     env.storage()
         .persistent()
-        .set(&DataKey::Stream(stream_id), stream);
+        .remove(&DataKey::Stream(stream_id));
+    bump_instance(env);
 }
 
 /// Returns the stream if it exists, `None` otherwise (used by read-only queries).
+///
+/// Extends the entry's TTL when found, and upgrades records written by an
+/// older schema (including pre-versioning bare records) via `migrate_stream`.
+/// A record in no known layout reads as missing.
 pub fn try_load_stream(env: &Env, stream_id: u64) -> Option<Stream> {
-    env.storage().persistent().get(&DataKey::Stream(stream_id))
+    let key = DataKey::Stream(stream_id);
+    let raw: Val = env.storage().persistent().get(&key)?;
+    let stored = decode_stream(env, &raw)?;
+    bump_persistent(env, &key);
+    bump_instance(env);
+    Some(migrate_stream(stored))
+}
+
+/// Decodes a stored stream record into its `VersionedStream` envelope.
+///
+/// Records written before schema versioning hold a bare, untagged
+/// `StreamV0`; they are wrapped as `V0` here. The host traps, rather than
+/// returning an error, when asked to decode an unknown variant name or a
+/// struct from a map with other fields, so the record's shape is checked
+/// before it is decoded.
+fn decode_stream(env: &Env, raw: &Val) -> Option<VersionedStream> {
+    if let Ok(tagged) = Vec::<Val>::try_from_val(env, raw) {
+        let tag = Symbol::try_from_val(env, &tagged.get(0)?).ok()?;
+        if tag != Symbol::new(env, "V0") && tag != Symbol::new(env, "V1") {
+            return None;
+        }
+        return VersionedStream::try_from_val(env, raw).ok();
+    }
+    let fields = Map::<Symbol, Val>::try_from_val(env, raw).ok()?;
+    if fields.len() != 9 || !fields.contains_key(Symbol::new(env, "is_active")) {
+        return None;
+    }
+    StreamV0::try_from_val(env, raw)
+        .ok()
+        .map(VersionedStream::V0)
+}
+
+/// Extends the TTL of an existing stream entry and of its index positions.
+///
+/// Returns `StreamNotFound` if no entry exists.
+pub fn extend_stream_ttl(env: &Env, stream_id: u64) -> Result<(), StreamError> {
+    let key = DataKey::Stream(stream_id);
+    if !env.storage().persistent().has(&key) {
+        return Err(StreamError::StreamNotFound);
+    }
+    bump_persistent(env, &key);
+    bump_index_slots(env, stream_id);
+    bump_instance(env);
+    Ok(())
+}
+
+// ─── Address Indexes ─────────────────────────────────────────────────────────
+
+/// Number of stream IDs stored per address index page.
+///
+/// Bounds the size of every index entry no matter how many streams an
+/// address accumulates.
+pub const INDEX_PAGE_SIZE: u32 = 50;
+
+/// The two address indexes every stream is listed in.
+#[derive(Clone, Copy)]
+pub enum StreamIndex {
+    /// Streams by sender (`DataKey::SenderStreams`).
+    Sender,
+    /// Streams by recipient (`DataKey::RecipientStreams`).
+    Recipient,
+}
+
+impl StreamIndex {
+    fn page_key(self, owner: &Address, page: u32) -> DataKey {
+        match self {
+            StreamIndex::Sender => DataKey::SenderStreams(owner.clone(), page),
+            StreamIndex::Recipient => DataKey::RecipientStreams(owner.clone(), page),
+        }
+    }
+
+    fn count_key(self, owner: &Address) -> DataKey {
+        match self {
+            StreamIndex::Sender => DataKey::SenderStreamCount(owner.clone()),
+            StreamIndex::Recipient => DataKey::RecipientStreamCount(owner.clone()),
+        }
+    }
+
+    fn slot_key(self, stream_id: u64) -> DataKey {
+        match self {
+            StreamIndex::Sender => DataKey::SenderStreamSlot(stream_id),
+            StreamIndex::Recipient => DataKey::RecipientStreamSlot(stream_id),
+        }
+    }
+}
+
+/// Returns how many stream IDs are listed under `owner` in `index`.
+pub fn index_len(env: &Env, index: StreamIndex, owner: &Address) -> u32 {
+    let key = index.count_key(owner);
+    match env.storage().persistent().get(&key) {
+        Some(count) => {
+            bump_persistent(env, &key);
+            count
+        }
+        None => 0,
+    }
+}
+
+/// Loads one page of `owner`'s IDs in `index`; empty if the page does not
+/// exist. Extends the entry's TTL when found.
+fn load_index_page(env: &Env, index: StreamIndex, owner: &Address, page: u32) -> Vec<u64> {
+    let key = index.page_key(owner, page);
+    match env.storage().persistent().get(&key) {
+        Some(ids) => {
+            bump_persistent(env, &key);
+            ids
+        }
+        None => Vec::new(env),
+    }
+}
+
+/// Persists one index page, deleting the entry once it is empty.
+fn save_index_page(env: &Env, index: StreamIndex, owner: &Address, page: u32, ids: &Vec<u64>) {
+    let key = index.page_key(owner, page);
+    if ids.is_empty() {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, ids);
+        bump_persistent(env, &key);
+    }
+}
+
+/// Records `stream_id`'s position in `index`.
+fn save_index_slot(env: &Env, index: StreamIndex, stream_id: u64, slot: u32) {
+    let key = index.slot_key(stream_id);
+    env.storage().persistent().set(&key, &slot);
+    bump_persistent(env, &key);
+}
+
+/// Returns up to `limit` of `owner`'s IDs in `index`, skipping the first
+/// `start`. Only the pages overlapping the requested range are read.
+pub fn load_index(
+    env: &Env,
+    index: StreamIndex,
+    owner: &Address,
+    start: u32,
+    limit: u32,
+) -> Vec<u64> {
+    let mut ids = Vec::new(env);
+    let end = start
+        .saturating_add(limit)
+        .min(index_len(env, index, owner));
+    let mut pos = start;
+    while pos < end {
+        let page = load_index_page(env, index, owner, pos / INDEX_PAGE_SIZE);
+        let from = pos % INDEX_PAGE_SIZE;
+        let to = (from + (end - pos)).min(page.len());
+        ids.append(&page.slice(from..to));
+        pos += INDEX_PAGE_SIZE - from;
+    }
+    ids
+}
+
+/// Appends `stream_id` to `owner`'s IDs in `index`.
+pub fn add_to_index(env: &Env, index: StreamIndex, owner: &Address, stream_id: u64) {
+    let slot = index_len(env, index, owner);
+    let page = slot / INDEX_PAGE_SIZE;
+    let mut ids = load_index_page(env, index, owner, page);
+    ids.push_back(stream_id);
+    save_index_page(env, index, owner, page, &ids);
+    save_index_slot(env, index, stream_id, slot);
+
+    let count_key = index.count_key(owner);
+    env.storage().persistent().set(&count_key, &(slot + 1));
+    bump_persistent(env, &count_key);
+}
+
+/// Removes `stream_id` from `owner`'s IDs in `index`, if present.
+///
+/// The last ID in the index is moved into the freed slot, so removal
+/// touches at most two pages regardless of the index size.
+pub fn remove_from_index(env: &Env, index: StreamIndex, owner: &Address, stream_id: u64) {
+    let slot_key = index.slot_key(stream_id);
+    let Some(slot) = env.storage().persistent().get::<_, u32>(&slot_key) else {
+        return;
+    };
+    env.storage().persistent().remove(&slot_key);
+
+    let last = index_len(env, index, owner) - 1;
+    let last_page = last / INDEX_PAGE_SIZE;
+    let mut tail = load_index_page(env, index, owner, last_page);
+    let moved = tail.pop_back_unchecked();
+    if slot != last {
+        let page = slot / INDEX_PAGE_SIZE;
+        if page == last_page {
+            tail.set(slot % INDEX_PAGE_SIZE, moved);
+        } else {
+            let mut ids = load_index_page(env, index, owner, page);
+            ids.set(slot % INDEX_PAGE_SIZE, moved);
+            save_index_page(env, index, owner, page, &ids);
+        }
+        save_index_slot(env, index, moved, slot);
+    }
+    save_index_page(env, index, owner, last_page, &tail);
+
+    let count_key = index.count_key(owner);
+    if last == 0 {
+        env.storage().persistent().remove(&count_key);
+    } else {
+        env.storage().persistent().set(&count_key, &last);
+        bump_persistent(env, &count_key);
+    }
+}
+
+/// Extends the TTL of `stream_id`'s position entries in both indexes.
+fn bump_index_slots(env: &Env, stream_id: u64) {
+    for index in [StreamIndex::Sender, StreamIndex::Recipient] {
+        let key = index.slot_key(stream_id);
+        if env.storage().persistent().has(&key) {
+            bump_persistent(env, &key);
+        }
+    }
+}
+
+// ─── Subscriptions ───────────────────────────────────────────────────────────
+
+/// Returns the next subscription ID and persists the updated counter.
+///
+/// Subscriptions have their own counter, independent of stream IDs.
+/// IDs start at 1.
+pub fn next_subscription_id(env: &Env) -> u64 {
+    let id: u64 = env
+        .storage()
+        .instance()
+        .get(&DataKey::SubscriptionCounter)
+        .unwrap_or(0)
+        + 1;
+    env.storage()
+        .instance()
+        .set(&DataKey::SubscriptionCounter, &id);
+    bump_instance(env);
+    id
+}
+
+/// Loads a subscription by ID from persistent storage, extending its TTL.
+///
+/// Returns `SubscriptionNotFound` if no entry exists.
+pub fn load_subscription(env: &Env, subscription_id: u64) -> Result<Subscription, StreamError> {
+    try_load_subscription(env, subscription_id).ok_or(StreamError::SubscriptionNotFound)
+}
+
+/// Persists a subscription record in persistent storage.
+pub fn save_subscription(env: &Env, subscription_id: u64, subscription: &Subscription) {
+    let key = DataKey::Subscription(subscription_id);
+    env.storage().persistent().set(&key, subscription);
+    bump_persistent(env, &key);
+    bump_instance(env);
+}
+
+/// Returns the subscription if it exists, `None` otherwise (used by
+/// read-only queries). Extends the entry's TTL when found.
+pub fn try_load_subscription(env: &Env, subscription_id: u64) -> Option<Subscription> {
+    let key = DataKey::Subscription(subscription_id);
+    let subscription = env.storage().persistent().get(&key)?;
+    bump_persistent(env, &key);
+    bump_instance(env);
+    Some(subscription)
 }
 
 // ─── Protocol Config ──────────────────────────────────────────────────────────
@@ -59,10 +478,7 @@ pub fn config_exists(env: &Env) -> bool {
 ///
 /// Returns `NotInitialized` if `initialize` has not been called yet.
 pub fn load_config(env: &Env) -> Result<ProtocolConfig, StreamError> {
-    env.storage()
-        .instance()
-        .get(&DataKey::ProtocolConfig)
-        .ok_or(StreamError::NotInitialized)
+    try_load_config(env).ok_or(StreamError::NotInitialized)
 }
 
 /// Persists the protocol config.
@@ -70,10 +486,29 @@ pub fn save_config(env: &Env, config: &ProtocolConfig) {
     env.storage()
         .instance()
         .set(&DataKey::ProtocolConfig, config);
+    bump_instance(env);
 }
 
 /// Reads the protocol config as an `Option` (returns `None` if unset).
 /// Used by optional fee-collection logic.
 pub fn try_load_config(env: &Env) -> Option<ProtocolConfig> {
+    bump_instance(env);
     env.storage().instance().get(&DataKey::ProtocolConfig)
 }
+
+/// Returns the admin proposed by `propose_admin`, if any.
+pub fn load_pending_admin(env: &Env) -> Option<Address> {
+    bump_instance(env);
+    env.storage().instance().get(&DataKey::PendingAdmin)
+}
+
+/// Records `admin` as the pending admin, replacing any earlier proposal.
+pub fn save_pending_admin(env: &Env, admin: &Address) {
+    env.storage().instance().set(&DataKey::PendingAdmin, admin);
+    bump_instance(env);
+}
+
+/// Clears the pending admin slot.
+pub fn clear_pending_admin(env: &Env) {
+    env.storage().instance().remove(&DataKey::PendingAdmin);
+}
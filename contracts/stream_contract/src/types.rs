@@ -17,6 +17,28 @@ pub enum DataKey {
     Stream(u64),
     /// Protocol-level fee configuration (singleton).
     ProtocolConfig,
+    /// Admin proposed by `propose_admin`, awaiting `accept_admin` (singleton).
+    PendingAdmin,
+    /// Storage schema version the instance was last migrated to (singleton).
+    SchemaVersion,
+    /// One page of the IDs of the streams currently sent by an address;
+    /// see `storage::INDEX_PAGE_SIZE`.
+    SenderStreams(Address, u32),
+    /// Number of IDs across an address's `SenderStreams` pages.
+    SenderStreamCount(Address),
+    /// Position of a stream's ID in its sender's `SenderStreams` pages.
+    SenderStreamSlot(u64),
+    /// One page of the IDs of the streams currently paying an address;
+    /// see `storage::INDEX_PAGE_SIZE`.
+    RecipientStreams(Address, u32),
+    /// Number of IDs across an address's `RecipientStreams` pages.
+    RecipientStreamCount(Address),
+    /// Position of a stream's ID in its recipient's `RecipientStreams` pages.
+    RecipientStreamSlot(u64),
+    /// Global monotonic counter for assigning subscription IDs.
+    SubscriptionCounter,
+    /// Individual subscription record, keyed by its unique u64 ID.
+    Subscription(u64),
 }
 
 /// Immutable state of a payment stream.
@@ -38,24 +60,243 @@ pub struct Stream {
     pub deposited_amount: i128,
     /// Cumulative amount already withdrawn by the recipient.
     pub withdrawn_amount: i128,
-    /// Ledger timestamp at stream creation.
+    /// Ledger timestamp at which accrual begins.
+    pub start_time: u64,
+    /// Ledger timestamp at which the schedule is fully streamed.
+    pub end_time: u64,
+    /// Nothing is claimable before this timestamp. Equals `start_time` for
+    /// streams without a cliff.
+    pub cliff_time: u64,
+    /// Who may cancel this stream. Fixed at creation.
+    pub cancelability: Cancelability,
+    /// Where payouts come from. Fixed at creation.
+    pub funding: FundingMode,
+    /// Ledger timestamp from which the current `rate_per_second` applies.
+    /// Equals `start_time` until a top-up changes the rate.
+    pub checkpoint_time: u64,
+    /// Cumulative amount already streamed at `checkpoint_time`.
+    pub checkpoint_streamed: i128,
+    /// Ledger timestamp of the last state mutation. Informational only;
+    /// accrual is derived from the schedule, never from this field.
+    pub last_update_time: u64,
+    /// Lifecycle status; see `StreamStatus`.
+    pub status: StreamStatus,
+    /// Ledger timestamp of the most recent `pause_stream`; 0 if never paused.
+    pub paused_at: u64,
+    /// Accrued balance frozen at cancellation, claimable once by the recipient
+    /// via `claim_after_cancel`. Always 0 for streams that were never cancelled.
+    pub frozen_accrued_amount: i128,
+}
+
+/// On-ledger envelope for `Stream`, tagged with the layout it was written in.
+///
+/// Stored under `DataKey::Stream(id)`. When `Stream` changes shape, freeze
+/// the old layout as a new struct, add a variant for it, and convert it in
+/// `storage::migrate_stream`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum VersionedStream {
+    /// Original layout. Written bare, without this envelope, by every build
+    /// before schema versioning; see `storage::try_load_stream`.
+    V0(StreamV0),
+    V1(Stream),
+}
+
+/// Layout of `Stream` before schema versioning, when accrual ran from
+/// `last_update_time` and `is_active` was the only lifecycle state. Only
+/// read by `storage::migrate_stream`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StreamV0 {
+    pub sender: Address,
+    pub recipient: Address,
+    pub token_address: Address,
+    pub rate_per_second: i128,
+    pub deposited_amount: i128,
+    pub withdrawn_amount: i128,
     pub start_time: u64,
-    /// Ledger timestamp of the last state mutation.
     pub last_update_time: u64,
-    /// `false` once fully withdrawn or cancelled.
     pub is_active: bool,
 }
 
+/// Lifecycle status of a stream.
+///
+/// `Scheduled`, `Streaming` and `Depleted` follow from the ledger time and the
+/// schedule; every other transition is made explicitly by an entrypoint.
+/// `Cancelled` and `Completed` are terminal.
+#[contracttype]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum StreamStatus {
+    /// Funded, but `start_time` has not been reached yet.
+    Scheduled = 0,
+    /// Tokens accrue according to the schedule.
+    Streaming = 1,
+    /// Accrual is frozen at `paused_at` until the sender resumes.
+    Paused = 2,
+    /// Cancelled by the sender; unaccrued funds have been refunded.
+    Cancelled = 3,
+    /// The recipient has withdrawn the entire deposit.
+    Completed = 4,
+    /// The whole deposit has streamed; the recipient has yet to withdraw all of it.
+    Depleted = 5,
+}
+
+/// Cancellation policy chosen by the sender in `create_stream`.
+#[contracttype]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum Cancelability {
+    /// Nobody can cancel. For escrowed streams the recipient is guaranteed
+    /// the full deposit; allowance-funded streams only pay what the sender
+    /// still covers, since the sender can revoke the allowance at any time.
+    NonCancelable = 0,
+    /// Only the sender may cancel.
+    SenderOnly = 1,
+    /// Either the sender or the recipient may cancel.
+    Either = 2,
+}
+
+/// How a stream's payouts are funded.
+#[contracttype]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum FundingMode {
+    /// The full deposit is held by the contract from creation.
+    Escrowed = 0,
+    /// Nothing is held; each payout is pulled from the sender via
+    /// `transfer_from` against an allowance granted to the contract.
+    Allowance = 1,
+}
+
+/// Parameters for one stream in `create_streams_batch`.
+///
+/// Every stream in a batch shares the sender and token of the call.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StreamParams {
+    /// Address entitled to withdraw from the stream.
+    pub recipient: Address,
+    /// Gross amount to escrow, before protocol fee deduction.
+    pub amount: i128,
+    /// Future start timestamp, or `None` to start immediately.
+    pub start_time: Option<u64>,
+    /// Seconds from start to end.
+    pub duration: u64,
+    /// Cliff timestamp, or `None` for no cliff.
+    pub cliff_time: Option<u64>,
+    /// Who may cancel the stream.
+    pub cancelability: Cancelability,
+}
+
+/// Per-stream outcome of `withdraw_many`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WithdrawalResult {
+    /// Stream the entry refers to.
+    pub stream_id: u64,
+    /// Amount paid from this stream; 0 when the stream was skipped.
+    pub amount: i128,
+}
+
+/// Snapshot of a stream's balance at a point in time, as returned by
+/// `get_stream_balance`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StreamBalance {
+    /// Timestamp the snapshot was evaluated at.
+    pub timestamp: u64,
+    /// Net tokens deposited into the stream.
+    pub deposited: i128,
+    /// Cumulative tokens accrued to the recipient by `timestamp`.
+    pub streamed: i128,
+    /// Tokens the recipient has already withdrawn.
+    pub withdrawn: i128,
+    /// Tokens the recipient could withdraw at `timestamp`.
+    pub claimable: i128,
+    /// Tokens the sender would get back if cancelled at `timestamp`; always
+    /// 0 for allowance-funded streams.
+    pub refundable: i128,
+}
+
+/// How `top_up_stream` applies newly deposited funds to the schedule.
+#[contracttype]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum TopUpMode {
+    /// Keep `rate_per_second` and push `end_time` out.
+    ExtendDuration = 0,
+    /// Keep `end_time` and raise `rate_per_second` for the remaining time.
+    RaiseRate = 1,
+}
+
 /// Protocol-wide fee configuration.
 ///
 /// Stored as a singleton in instance storage under `DataKey::ProtocolConfig`.
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ProtocolConfig {
-    /// Address with authority to update this configuration.
-    pub admin: Address,
+    /// Address with authority to update this configuration; `None` once
+    /// renounced via `renounce_admin`.
+    pub admin: Option<Address>,
     /// Address that receives protocol fees.
     pub treasury: Address,
     /// Fee expressed in basis points (1 bps = 0.01%). Max: 1 000 bps = 10%.
     pub fee_rate_bps: u32,
+    /// Emergency stop set by `pause_protocol`: blocks new deposits while
+    /// leaving exits open.
+    pub paused: bool,
+}
+
+/// Layout of `ProtocolConfig` at schema version 1, before `paused` was
+/// added. Only read by `storage::migrate_instance`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProtocolConfigV1 {
+    pub admin: Option<Address>,
+    pub treasury: Address,
+    pub fee_rate_bps: u32,
+}
+
+/// A recurring fixed-amount payment from `subscriber` to `merchant`.
+///
+/// Unlike a stream nothing is escrowed: each period is pulled from the
+/// subscriber by `charge_subscription` using a token allowance granted to
+/// this contract. Stored in persistent storage under
+/// `DataKey::Subscription(id)`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Subscription {
+    /// Address that pays each period.
+    pub subscriber: Address,
+    /// Address that receives each period's payment.
+    pub merchant: Address,
+    /// Token the subscription is paid in.
+    pub token_address: Address,
+    /// Amount charged per period.
+    pub amount_per_period: i128,
+    /// Length of one period in seconds.
+    pub period: u64,
+    /// Total number of periods to charge before the subscription completes.
+    pub max_periods: u32,
+    /// Number of periods charged so far.
+    pub periods_charged: u32,
+    /// Ledger timestamp at which the first period is due. Period `n`
+    /// (0-based) is due at `start_time + n * period`.
+    pub start_time: u64,
+    /// Lifecycle status; see `SubscriptionStatus`.
+    pub status: SubscriptionStatus,
+}
+
+/// Lifecycle status of a subscription. `Cancelled` and `Completed` are terminal.
+#[contracttype]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum SubscriptionStatus {
+    /// Periods are charged as they fall due.
+    Active = 0,
+    /// Cancelled by the subscriber or merchant; no further charges.
+    Cancelled = 1,
+    /// All `max_periods` have been charged.
+    Completed = 2,
 }